@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+
+use application::ports::out_::Clock;
+
+/// Wall-clock time.
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    async fn sleep_until(
+        &self,
+        deadline: Instant,
+    ) {
+        tokio::time::sleep_until(deadline.into()).await;
+    }
+}
+
+/// Virtual time that stands still until `advance` is called, for tests and
+/// simulations that want to fast-forward through games.
+pub struct ManualClock {
+    origin: Instant,
+    /// Calendar time at `origin`.
+    wall_origin: SystemTime,
+    elapsed: watch::Sender<Duration>,
+}
+
+impl ManualClock {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            wall_origin: SystemTime::now(),
+            elapsed: watch::Sender::new(Duration::ZERO),
+        }
+    }
+
+    /// Moves time forward, waking every sleeper whose deadline has passed.
+    pub fn advance(
+        &self,
+        by: Duration,
+    ) {
+        self.elapsed.send_modify(|elapsed| *elapsed += by);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.borrow()
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    fn wall_time(&self) -> SystemTime {
+        self.wall_origin + self.elapsed()
+    }
+
+    async fn sleep_until(
+        &self,
+        deadline: Instant,
+    ) {
+        let mut elapsed = self.elapsed.subscribe();
+        loop {
+            let now = self.origin + *elapsed.borrow_and_update();
+            if now >= deadline {
+                return;
+            }
+            // The sender lives as long as `self`, so this never errors
+            let _ = elapsed.changed().await;
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+
+use application::ports::out_::Credentials;
+use domain::PlayerId;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signed ahead of a resume token's payload, so that resume and session
+/// tokens cannot stand in for each other.
+const RESUME_SCOPE: &str = "resume";
+
+/// Argon2 password hashes, and session and resume tokens of the form
+/// `<player id>.<expiry>.<signature>` signed with HMAC-SHA256.
+pub struct HmacCredentials {
+    key: Vec<u8>,
+    session_ttl: Duration,
+    resume_ttl: Duration,
+}
+
+impl HmacCredentials {
+    pub fn new(
+        key: impl Into<Vec<u8>>,
+        session_ttl: Duration,
+        resume_ttl: Duration,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            session_ttl,
+            resume_ttl,
+        }
+    }
+
+    /// Signs with the key saved in `path`, generating and saving a random one
+    /// the first time so that tokens stay valid across restarts.
+    pub async fn open(
+        path: impl Into<PathBuf>,
+        session_ttl: Duration,
+        resume_ttl: Duration,
+    ) -> io::Result<Self> {
+        let path = path.into();
+        match tokio::fs::read(&path).await {
+            Ok(key) => return Ok(Self::new(key, session_ttl, resume_ttl)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        let mut key = [0; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        let key = URL_SAFE_NO_PAD.encode(key);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let partial = path.with_extension("tmp");
+        tokio::fs::write(&partial, &key).await?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o600)).await?;
+        }
+        tokio::fs::rename(&partial, &path).await?;
+        Ok(Self::new(key, session_ttl, resume_ttl))
+    }
+
+    /// The scope goes in behind its length, so no split of the same bytes
+    /// between scope and payload signs the same.
+    fn mac(
+        &self,
+        scope: &str,
+        payload: &str,
+    ) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(&(scope.len() as u64).to_be_bytes());
+        mac.update(scope.as_bytes());
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    fn issue(
+        &self,
+        scope: &str,
+        player_id: PlayerId,
+        ttl: Duration,
+    ) -> String {
+        let expires = unix_now() + ttl.as_secs();
+        let payload = format!("{}.{expires}", player_id.0);
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(scope, &payload).finalize().into_bytes());
+        format!("{payload}.{signature}")
+    }
+
+    fn verify(
+        &self,
+        scope: &str,
+        token: &str,
+    ) -> Option<PlayerId> {
+        let (payload, signature) = token.rsplit_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        self.mac(scope, payload).verify_slice(&signature).ok()?;
+
+        let (player_id, expires) = payload.split_once('.')?;
+        if expires.parse::<u64>().ok()? <= unix_now() {
+            return None;
+        }
+        uuid::Uuid::parse_str(player_id).ok().map(PlayerId)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+impl Credentials for HmacCredentials {
+    fn hash_password(
+        &self,
+        password: &str,
+    ) -> String {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("default Argon2 parameters are valid")
+            .to_string()
+    }
+
+    fn verify_password(
+        &self,
+        password: &str,
+        hash: &str,
+    ) -> bool {
+        PasswordHash::new(hash).is_ok_and(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+    }
+
+    fn issue_token(
+        &self,
+        player_id: PlayerId,
+    ) -> String {
+        self.issue("", player_id, self.session_ttl)
+    }
+
+    fn verify_token(
+        &self,
+        token: &str,
+    ) -> Option<PlayerId> {
+        self.verify("", token)
+    }
+
+    fn issue_resume_token(
+        &self,
+        player_id: PlayerId,
+    ) -> String {
+        self.issue(RESUME_SCOPE, player_id, self.resume_ttl)
+    }
+
+    fn verify_resume_token(
+        &self,
+        token: &str,
+    ) -> Option<PlayerId> {
+        self.verify(RESUME_SCOPE, token)
+    }
+}
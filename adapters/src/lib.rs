@@ -1,5 +1,13 @@
+mod clock;
+mod credentials;
+mod persistence;
 mod web;
 
+pub use clock::{ManualClock, SystemClock};
+pub use credentials::HmacCredentials;
+pub use persistence::{FileGameRepository, FileMatchResultRepository, FilePlayerRepository, InMemoryGameRepository};
 pub use web::{
-    AppState, GetQueueResponse, IncomingMessage, WebSocketNotifier, create_app_state, get_queue, handle_connection,
+    AccountErrorResponse, AppState, ConnectParams, ConnectionId, GetQueueResponse, IncomingMessage, LoginRequest,
+    RegisterRequest, WebSocketNotifier, create_app_state, get_match, get_player_matches, get_queue, get_stats,
+    handle_connection, login, register,
 };
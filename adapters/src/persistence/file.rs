@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use application::ports::out_::{GameRepository, GameServiceError, GameSnapshot};
+use domain::GameId;
+
+use super::InMemoryGameRepository;
+
+#[derive(Serialize, Deserialize)]
+struct StoredGame {
+    game_id: GameId,
+    snapshot: GameSnapshot,
+}
+
+/// Games held in memory and written to one JSON file each in `dir`.
+///
+/// Updates only mark a game as changed; `flush` writes the changed ones out.
+/// Games are updated on every tick, so writing through would mean a file per
+/// game several times a second.
+pub struct FileGameRepository {
+    dir: PathBuf,
+    games: InMemoryGameRepository,
+    /// Games changed since the last flush. Only locked long enough to mark or
+    /// take ids, so updates never wait on the disk.
+    dirty: Mutex<HashSet<GameId>>,
+    /// Held while files are written or deleted, so a game removed during a
+    /// flush cannot be written back.
+    files: Mutex<()>,
+}
+
+impl FileGameRepository {
+    /// Opens `dir`, creating it if needed, and loads every snapshot in it.
+    /// Snapshots that cannot be read are skipped.
+    pub async fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let games = InMemoryGameRepository::new();
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            match read_snapshot(&path).await {
+                Ok(stored) => games.insert(stored.game_id, stored.snapshot).await,
+                Err(e) => warn!(path = %path.display(), error = %e, "Skipping unreadable game snapshot"),
+            }
+        }
+        info!(dir = %dir.display(), games = games.list().await.len(), "Opened game snapshots");
+
+        Ok(Self {
+            dir,
+            games,
+            dirty: Mutex::new(HashSet::new()),
+            files: Mutex::new(()),
+        })
+    }
+
+    fn path(
+        &self,
+        game_id: GameId,
+    ) -> PathBuf {
+        self.dir.join(format!("{}.json", game_id.0))
+    }
+
+    /// Writes every game changed since the last flush. Games that fail to
+    /// write stay marked for the next flush, and the first error is returned
+    /// once the rest have been tried.
+    pub async fn flush(&self) -> io::Result<()> {
+        let _files = self.files.lock().await;
+        let changed: Vec<GameId> = self.dirty.lock().await.drain().collect();
+
+        let mut outcome = Ok(());
+        for game_id in changed {
+            let Some(snapshot) = self.games.get(game_id).await else {
+                continue;
+            };
+            if let Err(e) = self.write(game_id, snapshot).await {
+                self.dirty.lock().await.insert(game_id);
+                if outcome.is_ok() {
+                    outcome = Err(e);
+                }
+            }
+        }
+        outcome
+    }
+
+    async fn write(
+        &self,
+        game_id: GameId,
+        snapshot: GameSnapshot,
+    ) -> io::Result<()> {
+        let json = serde_json::to_vec(&StoredGame { game_id, snapshot })?;
+        // Written aside and renamed, so a crash mid-write leaves the old snapshot intact
+        let path = self.path(game_id);
+        let partial = path.with_extension("json.tmp");
+        tokio::fs::write(&partial, json).await?;
+        tokio::fs::rename(&partial, &path).await
+    }
+
+    /// Flushes every `interval` for as long as the process runs.
+    pub async fn run(
+        self: Arc<Self>,
+        interval: Duration,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.flush().await {
+                warn!(error = %e, "Failed to flush game snapshots");
+            }
+        }
+    }
+}
+
+async fn read_snapshot(path: &Path) -> io::Result<StoredGame> {
+    let bytes = tokio::fs::read(path).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+#[async_trait]
+impl GameRepository for FileGameRepository {
+    async fn get(
+        &self,
+        game_id: GameId,
+    ) -> Option<GameSnapshot> {
+        self.games.get(game_id).await
+    }
+
+    async fn insert(
+        &self,
+        game_id: GameId,
+        snapshot: GameSnapshot,
+    ) {
+        self.games.insert(game_id, snapshot).await;
+        self.dirty.lock().await.insert(game_id);
+    }
+
+    async fn update(
+        &self,
+        game_id: GameId,
+        snapshot: GameSnapshot,
+    ) -> Result<(), GameServiceError> {
+        self.games.update(game_id, snapshot).await?;
+        self.dirty.lock().await.insert(game_id);
+        Ok(())
+    }
+
+    async fn list(&self) -> Vec<GameId> {
+        self.games.list().await
+    }
+
+    async fn remove(
+        &self,
+        game_id: GameId,
+    ) -> Option<GameSnapshot> {
+        let _files = self.files.lock().await;
+        self.dirty.lock().await.remove(&game_id);
+        if let Err(e) = tokio::fs::remove_file(self.path(game_id)).await
+            && e.kind() != io::ErrorKind::NotFound
+        {
+            warn!(game_id = ?game_id, error = %e, "Failed to delete game snapshot");
+        }
+        self.games.remove(game_id).await
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use application::ports::out_::{MatchResult, MatchResultRepository};
+use domain::{GameId, PlayerId};
+
+/// Match results stored as one JSON file each in `dir`, and held in memory
+/// for lookups. Matches are saved once and never change, so every save is
+/// written straight away.
+pub struct FileMatchResultRepository {
+    dir: PathBuf,
+    results: RwLock<HashMap<GameId, MatchResult>>,
+}
+
+impl FileMatchResultRepository {
+    /// Opens `dir`, creating it if needed, and loads every result in it.
+    /// Files that cannot be read are skipped.
+    pub async fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let mut results = HashMap::new();
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let parsed = tokio::fs::read(&path)
+                .await
+                .and_then(|bytes| Ok(serde_json::from_slice::<MatchResult>(&bytes)?));
+            match parsed {
+                Ok(result) => {
+                    results.insert(result.game_id, result);
+                }
+                Err(e) => warn!(path = %path.display(), error = %e, "Skipping unreadable match result"),
+            }
+        }
+        info!(dir = %dir.display(), matches = results.len(), "Opened match history");
+
+        Ok(Self {
+            dir,
+            results: RwLock::new(results),
+        })
+    }
+
+    async fn write(
+        &self,
+        result: &MatchResult,
+    ) -> io::Result<()> {
+        let json = serde_json::to_vec(result)?;
+        // Written aside and renamed, so a crash mid-write cannot leave a truncated result behind
+        let path = self.dir.join(format!("{}.json", result.game_id.0));
+        let partial = path.with_extension("json.tmp");
+        tokio::fs::write(&partial, json).await?;
+        tokio::fs::rename(&partial, &path).await
+    }
+}
+
+#[async_trait]
+impl MatchResultRepository for FileMatchResultRepository {
+    async fn save(
+        &self,
+        result: MatchResult,
+    ) {
+        if let Err(e) = self.write(&result).await {
+            warn!(game_id = ?result.game_id, error = %e, "Failed to write match result");
+        }
+        self.results.write().await.insert(result.game_id, result);
+    }
+
+    async fn get(
+        &self,
+        game_id: GameId,
+    ) -> Option<MatchResult> {
+        self.results.read().await.get(&game_id).cloned()
+    }
+
+    async fn list_for_player(
+        &self,
+        player_id: PlayerId,
+    ) -> Vec<MatchResult> {
+        let mut matches: Vec<MatchResult> = self
+            .results
+            .read()
+            .await
+            .values()
+            .filter(|result| result.players.contains(&player_id))
+            .cloned()
+            .collect();
+        matches.sort_by_key(|result| std::cmp::Reverse(result.ended_at_ms));
+        matches
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use application::ports::out_::{GameRepository, GameServiceError, GameSnapshot};
+use domain::GameId;
+
+/// Games kept only for the life of the process.
+#[derive(Default)]
+pub struct InMemoryGameRepository {
+    games: RwLock<HashMap<GameId, GameSnapshot>>,
+}
+
+impl InMemoryGameRepository {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl GameRepository for InMemoryGameRepository {
+    async fn get(
+        &self,
+        game_id: GameId,
+    ) -> Option<GameSnapshot> {
+        self.games.read().await.get(&game_id).cloned()
+    }
+
+    async fn insert(
+        &self,
+        game_id: GameId,
+        snapshot: GameSnapshot,
+    ) {
+        self.games.write().await.insert(game_id, snapshot);
+    }
+
+    async fn update(
+        &self,
+        game_id: GameId,
+        snapshot: GameSnapshot,
+    ) -> Result<(), GameServiceError> {
+        let mut games = self.games.write().await;
+        let stored = games.get_mut(&game_id).ok_or(GameServiceError::GameNotFound(game_id))?;
+        *stored = snapshot;
+        Ok(())
+    }
+
+    async fn list(&self) -> Vec<GameId> {
+        self.games.read().await.keys().copied().collect()
+    }
+
+    async fn remove(
+        &self,
+        game_id: GameId,
+    ) -> Option<GameSnapshot> {
+        self.games.write().await.remove(&game_id)
+    }
+}
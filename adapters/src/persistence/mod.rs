@@ -0,0 +1,9 @@
+mod file;
+mod matches;
+mod memory;
+mod players;
+
+pub use file::FileGameRepository;
+pub use matches::FileMatchResultRepository;
+pub use memory::InMemoryGameRepository;
+pub use players::FilePlayerRepository;
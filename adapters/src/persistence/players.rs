@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use application::ports::out_::{AccountError, PlayerAccount, PlayerRepository};
+use domain::PlayerId;
+
+/// Accounts kept in memory and rewritten to a single JSON file on every
+/// registration, which is rare next to lookups.
+pub struct FilePlayerRepository {
+    path: PathBuf,
+    accounts: RwLock<HashMap<PlayerId, PlayerAccount>>,
+}
+
+impl FilePlayerRepository {
+    /// Loads the accounts in `path`, starting empty if the file does not exist yet.
+    pub async fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let accounts: Vec<PlayerAccount> = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        info!(path = %path.display(), accounts = accounts.len(), "Opened player accounts");
+
+        Ok(Self {
+            path,
+            accounts: RwLock::new(accounts.into_iter().map(|a| (a.player_id, a)).collect()),
+        })
+    }
+
+    async fn write(
+        &self,
+        accounts: &HashMap<PlayerId, PlayerAccount>,
+    ) -> io::Result<()> {
+        let json = serde_json::to_vec(&accounts.values().collect::<Vec<_>>())?;
+        let partial = self.path.with_extension("json.tmp");
+        tokio::fs::write(&partial, json).await?;
+        tokio::fs::rename(&partial, &self.path).await
+    }
+}
+
+#[async_trait]
+impl PlayerRepository for FilePlayerRepository {
+    async fn insert(
+        &self,
+        account: PlayerAccount,
+    ) -> Result<(), AccountError> {
+        let mut accounts = self.accounts.write().await;
+        if accounts.values().any(|a| a.username == account.username) {
+            return Err(AccountError::UsernameTaken);
+        }
+        let player_id = account.player_id;
+        accounts.insert(player_id, account);
+        if let Err(e) = self.write(&accounts).await {
+            // An account that was never saved would vanish on the next restart
+            accounts.remove(&player_id);
+            warn!(player_id = ?player_id, error = %e, "Failed to write player accounts");
+            return Err(AccountError::Storage);
+        }
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        player_id: PlayerId,
+    ) -> Option<PlayerAccount> {
+        self.accounts.read().await.get(&player_id).cloned()
+    }
+
+    async fn find_by_username(
+        &self,
+        username: &str,
+    ) -> Option<PlayerAccount> {
+        self.accounts.read().await.values().find(|a| a.username == username).cloned()
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use application::ports::in_::Session;
+use application::ports::out_::AccountError;
+
+use super::state::AppState;
+
+#[derive(Deserialize)]
+pub struct RegisterRequest {
+    username: String,
+    password: String,
+    #[serde(default)]
+    display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Serialize)]
+pub struct AccountErrorResponse {
+    error: &'static str,
+}
+
+fn rejection(err: AccountError) -> (StatusCode, Json<AccountErrorResponse>) {
+    let (status, error) = match err {
+        AccountError::UsernameTaken => (StatusCode::CONFLICT, "username_taken"),
+        AccountError::InvalidUsername => (StatusCode::BAD_REQUEST, "invalid_username"),
+        AccountError::InvalidDisplayName => (StatusCode::BAD_REQUEST, "invalid_display_name"),
+        AccountError::WeakPassword => (StatusCode::BAD_REQUEST, "weak_password"),
+        AccountError::InvalidCredentials => (StatusCode::UNAUTHORIZED, "invalid_credentials"),
+        AccountError::Storage => (StatusCode::INTERNAL_SERVER_ERROR, "storage_unavailable"),
+    };
+    (status, Json(AccountErrorResponse { error }))
+}
+
+pub async fn register(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<RegisterRequest>,
+) -> Result<(StatusCode, Json<Session>), (StatusCode, Json<AccountErrorResponse>)> {
+    state
+        .accounts
+        .register(&request.username, &request.password, request.display_name.as_deref())
+        .await
+        .map(|session| (StatusCode::CREATED, Json(session)))
+        .map_err(rejection)
+}
+
+pub async fn login(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<LoginRequest>,
+) -> Result<Json<Session>, (StatusCode, Json<AccountErrorResponse>)> {
+    state
+        .accounts
+        .login(&request.username, &request.password)
+        .await
+        .map(Json)
+        .map_err(rejection)
+}
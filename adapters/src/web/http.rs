@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
 use axum::Json;
-use axum::extract::State;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
 use serde::Serialize;
 
-use domain::PlayerId;
+use application::ports::in_::game_service::GameStats;
+use application::ports::out_::MatchResult;
+use domain::{GameId, PlayerId};
 
 use super::state::AppState;
 
@@ -20,3 +23,22 @@ pub async fn get_queue(State(state): State<Arc<AppState>>) -> Json<GetQueueRespo
     let count = players.len();
     Json(GetQueueResponse { players, count })
 }
+
+pub async fn get_stats(State(state): State<Arc<AppState>>) -> Json<GameStats> {
+    Json(state.game_store.stats().await)
+}
+
+/// Past matches of one player, most recent first.
+pub async fn get_player_matches(
+    State(state): State<Arc<AppState>>,
+    Path(player_id): Path<PlayerId>,
+) -> Json<Vec<MatchResult>> {
+    Json(state.match_results.list_for_player(player_id).await)
+}
+
+pub async fn get_match(
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<GameId>,
+) -> Result<Json<MatchResult>, StatusCode> {
+    state.match_results.get(game_id).await.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
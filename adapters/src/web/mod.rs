@@ -1,7 +1,9 @@
+mod accounts;
 mod http;
 mod state;
 mod websocket;
 
-pub use http::{GetQueueResponse, get_queue};
+pub use accounts::{AccountErrorResponse, LoginRequest, RegisterRequest, login, register};
+pub use http::{GetQueueResponse, get_match, get_player_matches, get_queue, get_stats};
 pub use state::{AppState, create_app_state};
-pub use websocket::{IncomingMessage, WebSocketNotifier, handle_connection};
+pub use websocket::{ConnectParams, ConnectionId, IncomingMessage, WebSocketNotifier, handle_connection};
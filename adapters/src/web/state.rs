@@ -1,43 +1,129 @@
-use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
-use tokio::sync::{Mutex as TokioMutex, RwLock};
+use tokio::sync::Mutex as TokioMutex;
+use tracing::{info, warn};
 
-use application::ports::in_::MatchmakingService;
-use application::ports::in_::game_service::GameStore;
-use application::ports::out_::QueueNotifier;
+use application::ports::in_::game_service::{self, GameRegistry};
+use application::ports::in_::{AccountService, GameLifecycle, LifecycleConfig, MatchmakingService};
+use application::ports::out_::{
+    Clock, Credentials, GameRepository, MatchResultRepository, PlayerRepository, Presence, QueueNotifier,
+};
 
 use super::websocket::WebSocketNotifier;
+use crate::clock::SystemClock;
+use crate::credentials::HmacCredentials;
+use crate::persistence::{FileGameRepository, FileMatchResultRepository, FilePlayerRepository};
+
+/// Where game snapshots, match history and accounts are written unless
+/// `POCKY_DATA_DIR` says otherwise.
+const DEFAULT_DATA_DIR: &str = "data";
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(2);
+const SESSION_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// Connections are handed a fresh resume token well before this runs out.
+const RESUME_TTL: Duration = Duration::from_secs(5 * 60);
 
 pub struct AppState {
     pub notifier: Arc<WebSocketNotifier>,
-    pub game_store: GameStore,
+    pub game_store: Arc<GameRegistry>,
     pub matchmaking_service: Arc<TokioMutex<MatchmakingService>>,
+    pub game_snapshots: Arc<FileGameRepository>,
+    pub match_results: Arc<dyn MatchResultRepository>,
+    pub accounts: Arc<AccountService>,
 }
 
 impl AppState {
     pub fn new(
         notifier: Arc<WebSocketNotifier>,
-        game_store: GameStore,
+        game_store: Arc<GameRegistry>,
         matchmaking_service: Arc<TokioMutex<MatchmakingService>>,
+        game_snapshots: Arc<FileGameRepository>,
+        match_results: Arc<dyn MatchResultRepository>,
+        accounts: Arc<AccountService>,
     ) -> Self {
         Self {
             notifier,
             game_store,
             matchmaking_service,
+            game_snapshots,
+            match_results,
+            accounts,
+        }
+    }
+
+    /// Writes out every snapshot not yet flushed, for a clean shutdown.
+    pub async fn flush_snapshots(&self) {
+        if let Err(e) = self.game_snapshots.flush().await {
+            warn!(error = %e, "Failed to flush game snapshots on shutdown");
         }
     }
 }
 
-pub fn create_app_state() -> Arc<AppState> {
+/// Builds the server's state, restoring any games that were in flight when
+/// the previous process stopped.
+pub async fn create_app_state() -> Arc<AppState> {
     let notifier = Arc::new(WebSocketNotifier::new());
-    let game_store = Arc::new(RwLock::new(HashMap::new()));
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+
+    let data_dir = std::env::var("POCKY_DATA_DIR").unwrap_or_else(|_| DEFAULT_DATA_DIR.to_string());
+    let game_snapshots = Arc::new(
+        FileGameRepository::open(format!("{data_dir}/games"))
+            .await
+            .expect("game snapshot directory should be usable"),
+    );
+    tokio::spawn(Arc::clone(&game_snapshots).run(SNAPSHOT_INTERVAL));
+    let repository: Arc<dyn GameRepository> = game_snapshots.clone();
+    let match_results: Arc<dyn MatchResultRepository> = Arc::new(
+        FileMatchResultRepository::open(format!("{data_dir}/matches"))
+            .await
+            .expect("match history directory should be usable"),
+    );
+    let players: Arc<dyn PlayerRepository> = Arc::new(
+        FilePlayerRepository::open(format!("{data_dir}/players.json"))
+            .await
+            .expect("player accounts should be readable"),
+    );
+    let credentials: Arc<dyn Credentials> = match std::env::var("POCKY_SESSION_SECRET") {
+        Ok(secret) => Arc::new(HmacCredentials::new(secret, SESSION_TTL, RESUME_TTL)),
+        Err(_) => {
+            let path = format!("{data_dir}/session.key");
+            info!(
+                path,
+                "POCKY_SESSION_SECRET is not set; signing sessions with a key kept in the data directory"
+            );
+            Arc::new(
+                HmacCredentials::open(path, SESSION_TTL, RESUME_TTL)
+                    .await
+                    .expect("session key should be readable or creatable"),
+            )
+        }
+    };
+    let accounts = Arc::new(AccountService::new(Arc::clone(&players), credentials));
+
+    let game_store = Arc::new(GameRegistry::new(
+        Arc::clone(&clock),
+        repository,
+        Arc::clone(&match_results),
+        players,
+    ));
+    let restored = game_service::restore_games(Arc::clone(&notifier), &game_store).await;
+    if restored > 0 {
+        info!(restored, "Resumed games from the last run");
+    }
+
     let queue_notifier: Arc<dyn QueueNotifier> = notifier.clone();
     let matchmaking_service = MatchmakingService::new(queue_notifier);
 
+    let presence: Arc<dyn Presence> = notifier.clone();
+    let lifecycle = GameLifecycle::new(Arc::clone(&game_store), presence, clock, LifecycleConfig::default());
+    tokio::spawn(lifecycle.run());
+
     Arc::new(AppState::new(
         notifier,
         game_store,
         Arc::new(TokioMutex::new(matchmaking_service)),
+        game_snapshots,
+        match_results,
+        accounts,
     ))
 }
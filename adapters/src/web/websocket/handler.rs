@@ -1,47 +1,210 @@
 use std::sync::Arc;
+use std::time::Duration;
 
-use axum::extract::State;
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
-use axum::response::IntoResponse;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use futures::StreamExt;
 use futures::stream::SplitStream;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
 use application::ports::in_::game_service;
 use application::ports::in_::game_service::GameUseCase;
-use domain::{GameId, MatchmakingOutcome, PlayerId};
+use domain::{GameId, MatchmakingOutcome, OfferId, OptionSpec, OrderSpec, PlayerId, Side};
 
+use super::notifier::ConnectionId;
 use crate::web::state::AppState;
 
+/// How often a connection is sent a fresh resume token, well inside the
+/// token's lifetime so the latest one is always usable after a drop.
+const RESUME_REFRESH: Duration = Duration::from_secs(60);
+
 #[derive(Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum IncomingMessage {
     JoinQueue,
     LeaveQueue,
-    PlaceBid { game_id: GameId, value: i32 },
-    PlaceAsk { game_id: GameId, value: i32 },
-    CancelBid { game_id: GameId, price: i32 },
-    CancelAsk { game_id: GameId, price: i32 },
+    PlaceBid {
+        game_id: GameId,
+        value: i32,
+        #[serde(default = "one")]
+        quantity: u32,
+    },
+    PlaceAsk {
+        game_id: GameId,
+        value: i32,
+        #[serde(default = "one")]
+        quantity: u32,
+    },
+    CancelBid {
+        game_id: GameId,
+        price: i32,
+    },
+    CancelAsk {
+        game_id: GameId,
+        price: i32,
+    },
+    AmendOrder {
+        game_id: GameId,
+        side: Side,
+        price: i32,
+        new_price: i32,
+        new_quantity: u32,
+    },
+    CancelAll {
+        game_id: GameId,
+        #[serde(default)]
+        side: Option<Side>,
+    },
+    PlaceOco {
+        game_id: GameId,
+        first: OrderSpec,
+        second: OrderSpec,
+    },
+    PlaceBracket {
+        game_id: GameId,
+        entry_price: i32,
+        #[serde(default = "one")]
+        quantity: u32,
+        take_profit: i32,
+        stop_loss: i32,
+    },
+    Borrow {
+        game_id: GameId,
+        amount: i32,
+    },
+    Repay {
+        game_id: GameId,
+        amount: i32,
+    },
+    TradeIndex {
+        game_id: GameId,
+        side: Side,
+        #[serde(default = "one")]
+        quantity: u32,
+    },
+    BuyOption {
+        game_id: GameId,
+        spec: OptionSpec,
+    },
+    WriteOption {
+        game_id: GameId,
+        spec: OptionSpec,
+    },
+    ProposeOffer {
+        game_id: GameId,
+        buyer: PlayerId,
+        ticker: PlayerId,
+        #[serde(default = "one")]
+        quantity: u32,
+        price: i32,
+    },
+    RespondOffer {
+        game_id: GameId,
+        offer_id: OfferId,
+        accept: bool,
+    },
+    /// Asks for a fresh `game_resumed` snapshot after a sequence gap.
+    Resync {
+        game_id: GameId,
+    },
+}
+
+// Clients that predate order quantities only ever trade single shares
+fn one() -> u32 {
+    1
+}
+
+#[derive(Deserialize)]
+pub struct ConnectParams {
+    /// Session token from `/accounts/login`. Without one the player joins as
+    /// a guest under a fresh id.
+    #[serde(default)]
+    token: Option<String>,
+    /// Resume token from an earlier connection's `connected` message. Takes
+    /// precedence over `token`, and is ignored once it no longer verifies.
+    #[serde(default)]
+    resume: Option<String>,
+}
+
+/// First message on every connection.
+#[derive(Serialize)]
+#[serde(tag = "type", rename = "connected")]
+struct Connected {
+    player_id: PlayerId,
+    /// Present as `resume` when reconnecting to carry on as the same player.
+    resume_token: String,
+}
+
+/// Replaces the resume token from `Connected` before it expires.
+#[derive(Serialize)]
+#[serde(tag = "type", rename = "resume_token")]
+struct ResumeToken {
+    resume_token: String,
 }
 
 pub async fn handle_connection(
     ws: WebSocketUpgrade,
+    Query(params): Query<ConnectParams>,
     State(state): State<Arc<AppState>>,
-) -> impl IntoResponse {
+) -> Response {
+    let resumed = params.resume.and_then(|token| state.accounts.authenticate_resume(&token));
+    let player_id = match (resumed, params.token) {
+        (Some(player_id), _) => player_id,
+        (None, Some(token)) => match state.accounts.authenticate(&token) {
+            Some(player_id) => player_id,
+            None => return StatusCode::UNAUTHORIZED.into_response(),
+        },
+        (None, None) => PlayerId::new(),
+    };
+
     ws.on_upgrade(move |socket| async move {
-        let player_id = PlayerId::new();
-        info!(player_id = ?player_id, "Player connected");
+        info!(player_id = ?player_id, resumed = resumed.is_some(), "Player connected");
 
         let (sender, receiver) = socket.split();
-        state.notifier.register_player(player_id, sender).await;
+        let connection = state.notifier.register_player(player_id, sender).await;
+        let connected = Connected {
+            player_id,
+            resume_token: state.accounts.resume_token(player_id),
+        };
+        let message = serde_json::to_string(&connected).unwrap_or_default();
+        state.notifier.send_to_player(player_id, &message).await;
+        if game_service::resume_player(&state.game_store, player_id).await {
+            info!(player_id = ?player_id, "Player resumed their game");
+        }
 
-        handle_messages(player_id, receiver, state).await;
+        tokio::select! {
+            () = handle_messages(player_id, connection, receiver, Arc::clone(&state)) => {}
+            () = refresh_resume_token(player_id, &state) => {}
+        }
     })
+    .into_response()
+}
+
+/// Sends `player_id` a fresh resume token every `RESUME_REFRESH`, for as long
+/// as the connection lasts.
+async fn refresh_resume_token(
+    player_id: PlayerId,
+    state: &AppState,
+) {
+    let mut ticker = tokio::time::interval(RESUME_REFRESH);
+    // The first tick is immediate, and `Connected` already carried a token
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let refreshed = ResumeToken {
+            resume_token: state.accounts.resume_token(player_id),
+        };
+        let message = serde_json::to_string(&refreshed).unwrap_or_default();
+        state.notifier.send_to_player(player_id, &message).await;
+    }
 }
 
 async fn handle_messages(
     player_id: PlayerId,
+    connection: ConnectionId,
     mut receiver: SplitStream<WebSocket>,
     state: Arc<AppState>,
 ) {
@@ -51,7 +214,11 @@ async fn handle_messages(
 
             match serde_json::from_str::<IncomingMessage>(&text) {
                 Ok(incoming) => match incoming {
-                    IncomingMessage::PlaceBid { game_id, value } => {
+                    IncomingMessage::PlaceBid {
+                        game_id,
+                        value,
+                        quantity,
+                    } => {
                         let _ = game_service::execute(
                             Arc::clone(&state.notifier),
                             Arc::clone(&state.game_store),
@@ -59,11 +226,16 @@ async fn handle_messages(
                                 game_id,
                                 player_id,
                                 value,
+                                quantity,
                             },
                         )
                         .await;
                     }
-                    IncomingMessage::PlaceAsk { game_id, value } => {
+                    IncomingMessage::PlaceAsk {
+                        game_id,
+                        value,
+                        quantity,
+                    } => {
                         let _ = game_service::execute(
                             Arc::clone(&state.notifier),
                             Arc::clone(&state.game_store),
@@ -71,6 +243,7 @@ async fn handle_messages(
                                 game_id,
                                 player_id,
                                 value,
+                                quantity,
                             },
                         )
                         .await;
@@ -99,6 +272,180 @@ async fn handle_messages(
                         )
                         .await;
                     }
+                    IncomingMessage::AmendOrder {
+                        game_id,
+                        side,
+                        price,
+                        new_price,
+                        new_quantity,
+                    } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::AmendOrder {
+                                game_id,
+                                player_id,
+                                side,
+                                price,
+                                new_price,
+                                new_quantity,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::CancelAll { game_id, side } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::CancelAll {
+                                game_id,
+                                player_id,
+                                side,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::PlaceOco { game_id, first, second } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::PlaceOco {
+                                game_id,
+                                player_id,
+                                first,
+                                second,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::PlaceBracket {
+                        game_id,
+                        entry_price,
+                        quantity,
+                        take_profit,
+                        stop_loss,
+                    } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::PlaceBracket {
+                                game_id,
+                                player_id,
+                                entry_price,
+                                quantity,
+                                take_profit,
+                                stop_loss,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::Borrow { game_id, amount } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::Borrow {
+                                game_id,
+                                player_id,
+                                amount,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::Repay { game_id, amount } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::Repay {
+                                game_id,
+                                player_id,
+                                amount,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::TradeIndex { game_id, side, quantity } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::TradeIndex {
+                                game_id,
+                                player_id,
+                                side,
+                                quantity,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::BuyOption { game_id, spec } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::BuyOption {
+                                game_id,
+                                player_id,
+                                spec,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::WriteOption { game_id, spec } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::WriteOption {
+                                game_id,
+                                player_id,
+                                spec,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::ProposeOffer {
+                        game_id,
+                        buyer,
+                        ticker,
+                        quantity,
+                        price,
+                    } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::ProposeOffer {
+                                game_id,
+                                player_id,
+                                buyer,
+                                ticker,
+                                quantity,
+                                price,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::RespondOffer {
+                        game_id,
+                        offer_id,
+                        accept,
+                    } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::RespondOffer {
+                                game_id,
+                                player_id,
+                                offer_id,
+                                accept,
+                            },
+                        )
+                        .await;
+                    }
+                    IncomingMessage::Resync { game_id } => {
+                        let _ = game_service::execute(
+                            Arc::clone(&state.notifier),
+                            Arc::clone(&state.game_store),
+                            GameUseCase::Resync { game_id, player_id },
+                        )
+                        .await;
+                    }
                     IncomingMessage::JoinQueue => {
                         let mut matchmaking_s = state.matchmaking_service.lock().await;
                         let outcome = matchmaking_s.join_queue(player_id).await;
@@ -108,7 +455,7 @@ async fn handle_messages(
                                 Arc::clone(&state.game_store),
                                 GameUseCase::LaunchGame {
                                     players,
-                                    config: domain::GameConfig::default(),
+                                    config: Box::default(),
                                 },
                             )
                             .await;
@@ -128,5 +475,5 @@ async fn handle_messages(
     }
 
     info!(player_id = ?player_id, "Player disconnected");
-    state.notifier.unregister_player(player_id).await;
+    state.notifier.unregister_player(player_id, connection).await;
 }
@@ -1,5 +1,5 @@
 mod handler;
 mod notifier;
 
-pub use handler::{IncomingMessage, handle_connection};
-pub use notifier::WebSocketNotifier;
+pub use handler::{ConnectParams, IncomingMessage, handle_connection};
+pub use notifier::{ConnectionId, WebSocketNotifier};
@@ -1,51 +1,104 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use async_trait::async_trait;
 use axum::extract::ws::{Message, WebSocket};
 use futures::SinkExt;
 use futures::stream::SplitSink;
-use tokio::sync::{Mutex as TokioMutex, RwLock};
-use tracing::debug;
+use tokio::sync::{RwLock, mpsc};
+use tracing::{debug, warn};
 
-use application::ports::out_::{GameEventNotifier, GameNotification, QueueNotifier};
+use application::ports::out_::{GameEventNotifier, Presence, QueueNotifier, SequencedNotification};
 use domain::{MatchmakingOutcome, PlayerId};
 
 pub(crate) type WebSocketSender = SplitSink<WebSocket, Message>;
 
+/// Messages a connection can have waiting before it is taken to have stalled.
+const OUTBOUND_CAPACITY: usize = 256;
+
+/// Tells apart successive sockets bound to the same player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConnectionId(u64);
+
+struct Connection {
+    id: ConnectionId,
+    queue: mpsc::Sender<Message>,
+}
+
+/// Each connection gets an outbound queue drained by its own writer task, so
+/// messages reach a player in the order they were queued and a slow socket
+/// only ever holds up itself. A socket that falls too far behind is dropped
+/// instead of queued for without limit; the client resyncs on reconnecting.
 pub struct WebSocketNotifier {
-    connections: RwLock<Vec<(PlayerId, TokioMutex<WebSocketSender>)>>,
+    connections: RwLock<HashMap<PlayerId, Connection>>,
+    next_connection_id: AtomicU64,
 }
 
 impl WebSocketNotifier {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            connections: RwLock::new(Vec::new()),
+            connections: RwLock::new(HashMap::new()),
+            next_connection_id: AtomicU64::new(0),
         }
     }
 
+    /// Routes `player_id`'s messages to `sender`, taking over from any socket
+    /// they were already bound to.
     pub async fn register_player(
         &self,
         player_id: PlayerId,
-        sender: WebSocketSender,
-    ) {
-        self.connections.write().await.push((player_id, TokioMutex::new(sender)));
+        mut sender: WebSocketSender,
+    ) -> ConnectionId {
+        let (queue, mut outbound) = mpsc::channel(OUTBOUND_CAPACITY);
+        tokio::spawn(async move {
+            while let Some(message) = outbound.recv().await {
+                if sender.send(message).await.is_err() {
+                    return;
+                }
+            }
+            let _ = sender.close().await;
+        });
+        let id = ConnectionId(self.next_connection_id.fetch_add(1, Ordering::Relaxed));
+        self.connections.write().await.insert(player_id, Connection { id, queue });
+        id
     }
 
+    /// Dropping the queue lets the writer flush what is already queued and
+    /// close the socket.
+    /// Does nothing if the player has since reconnected on another socket.
     pub async fn unregister_player(
         &self,
         player_id: PlayerId,
+        connection: ConnectionId,
     ) {
-        self.connections.write().await.retain(|(pid, _)| *pid != player_id);
+        let mut connections = self.connections.write().await;
+        if connections.get(&player_id).is_some_and(|c| c.id == connection) {
+            connections.remove(&player_id);
+        }
     }
 
-    async fn send_to_player(
+    pub(crate) async fn send_to_player(
         &self,
         player_id: PlayerId,
         message: &str,
     ) {
         debug!(player_id = ?player_id, message = %message, "-> Sending");
-        let connections = self.connections.read().await;
-        if let Some((_, sender)) = connections.iter().find(|(pid, _)| *pid == player_id) {
-            let _ = sender.lock().await.send(Message::Text(message.into())).await;
+        let stalled = match self.connections.read().await.get(&player_id) {
+            Some(connection) if connection.queue.try_send(Message::Text(message.into())).is_err() => connection.id,
+            _ => return,
+        };
+        self.drop_stalled(vec![(player_id, stalled)]).await;
+    }
+
+    /// Unbinds connections whose queue is full or whose writer has gone.
+    async fn drop_stalled(
+        &self,
+        stalled: Vec<(PlayerId, ConnectionId)>,
+    ) {
+        for (player_id, connection) in stalled {
+            warn!(player_id = ?player_id, "Dropping connection that stopped keeping up");
+            self.unregister_player(player_id, connection).await;
         }
     }
 }
@@ -61,13 +114,23 @@ impl GameEventNotifier for WebSocketNotifier {
     async fn notify_player(
         &self,
         player_id: PlayerId,
-        notification: GameNotification,
+        notification: SequencedNotification,
     ) {
         let message = serde_json::to_string(&notification).unwrap_or_default();
         self.send_to_player(player_id, &message).await;
     }
 }
 
+#[async_trait]
+impl Presence for WebSocketNotifier {
+    async fn is_connected(
+        &self,
+        player_id: PlayerId,
+    ) -> bool {
+        self.connections.read().await.contains_key(&player_id)
+    }
+}
+
 #[async_trait]
 impl QueueNotifier for WebSocketNotifier {
     async fn broadcast(
@@ -75,10 +138,13 @@ impl QueueNotifier for WebSocketNotifier {
         event: &MatchmakingOutcome,
     ) {
         let message = serde_json::to_string(event).unwrap_or_default();
-        let connections = self.connections.read().await;
-        for (player_id, sender) in connections.iter() {
+        let mut stalled = Vec::new();
+        for (&player_id, connection) in self.connections.read().await.iter() {
             debug!(player_id = ?player_id, message = %message, "-> Broadcasting");
-            let _ = sender.lock().await.send(Message::Text(message.clone().into())).await;
+            if connection.queue.try_send(Message::Text(message.clone().into())).is_err() {
+                stalled.push((player_id, connection.id));
+            }
         }
+        self.drop_stalled(stalled).await;
     }
 }
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use adapters::{FilePlayerRepository, HmacCredentials};
+use application::ports::in_::AccountService;
+use application::ports::out_::{AccountError, Credentials, PlayerAccount, PlayerRepository};
+use domain::PlayerId;
+
+mod common;
+use common::TempDir;
+
+const TTL: Duration = Duration::from_secs(60);
+
+fn account(username: &str) -> PlayerAccount {
+    PlayerAccount {
+        player_id: PlayerId::new(),
+        username: username.to_string(),
+        display_name: username.to_string(),
+        password_hash: String::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_session_key_survives_restarts() {
+    let dir = TempDir::new();
+    let key = dir.path().join("session.key");
+    let player_id = PlayerId::new();
+
+    let token = HmacCredentials::open(&key, TTL, TTL).await.unwrap().issue_token(player_id);
+    let reopened = HmacCredentials::open(&key, TTL, TTL).await.unwrap();
+    assert_eq!(reopened.verify_token(&token), Some(player_id));
+
+    let elsewhere = HmacCredentials::open(dir.path().join("other.key"), TTL, TTL).await.unwrap();
+    assert_eq!(elsewhere.verify_token(&token), None);
+}
+
+#[tokio::test]
+async fn test_unsaved_accounts_are_rejected() {
+    let dir = TempDir::new();
+    let path = dir.path().join("players.json");
+    let players = FilePlayerRepository::open(&path).await.unwrap();
+
+    // A directory where the file is staged makes the write fail
+    let staging = path.with_extension("json.tmp");
+    std::fs::create_dir(&staging).unwrap();
+    assert_eq!(players.insert(account("alice")).await, Err(AccountError::Storage));
+    assert!(players.find_by_username("alice").await.is_none());
+
+    std::fs::remove_dir(&staging).unwrap();
+    assert_eq!(players.insert(account("alice")).await, Ok(()));
+    let reopened = FilePlayerRepository::open(&path).await.unwrap();
+    assert!(reopened.find_by_username("alice").await.is_some());
+}
+
+#[tokio::test]
+async fn test_register_then_log_in() {
+    let dir = TempDir::new();
+    let players = Arc::new(FilePlayerRepository::open(dir.path().join("players.json")).await.unwrap());
+    let credentials = Arc::new(HmacCredentials::new("secret", TTL, TTL));
+    let accounts = AccountService::new(players, credentials);
+
+    let registered = accounts.register("Alice", "correct horse", None).await.unwrap();
+    assert_eq!(registered.display_name, "Alice");
+    assert_eq!(accounts.authenticate(&registered.token), Some(registered.player_id));
+
+    let session = accounts.login("alice", "correct horse").await.unwrap();
+    assert_eq!(session.player_id, registered.player_id);
+    assert_eq!(
+        accounts.login("alice", "wrong horse").await.unwrap_err(),
+        AccountError::InvalidCredentials
+    );
+    assert_eq!(
+        accounts.register("ALICE", "another password", None).await.unwrap_err(),
+        AccountError::UsernameTaken
+    );
+}
+
+#[tokio::test]
+async fn test_resume_tokens_only_resume() {
+    let credentials = HmacCredentials::new("secret", TTL, TTL);
+    let player_id = PlayerId::new();
+
+    let resume = credentials.issue_resume_token(player_id);
+    let session = credentials.issue_token(player_id);
+    assert_eq!(credentials.verify_resume_token(&resume), Some(player_id));
+    assert_eq!(credentials.verify_token(&resume), None);
+    assert_eq!(credentials.verify_resume_token(&session), None);
+
+    let expired = HmacCredentials::new("secret", TTL, Duration::ZERO).issue_resume_token(player_id);
+    assert_eq!(credentials.verify_resume_token(&expired), None);
+}
@@ -0,0 +1,639 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+
+use async_trait::async_trait;
+
+use adapters::{FileGameRepository, FilePlayerRepository, HmacCredentials, InMemoryGameRepository, ManualClock};
+use application::ports::in_::game_service::{self, GameRegistry, GameUseCase};
+use application::ports::in_::{AccountService, GameLifecycle, LifecycleConfig};
+use application::ports::out_::{
+    AccountError, Clock, GameEventNotifier, GameNotification, GameRepository, MatchResult, MatchResultRepository,
+    PlayerAccount, PlayerRepository, Presence, SequencedNotification,
+};
+use domain::{GameAction, GameConfig, GameId, GamePhase, PlayerId};
+
+mod common;
+use common::TempDir;
+
+const TTL: Duration = Duration::from_secs(60);
+
+/// Every notification sent, in order.
+#[derive(Default)]
+struct Recorder {
+    sent: Mutex<Vec<(PlayerId, SequencedNotification)>>,
+}
+
+#[async_trait]
+impl GameEventNotifier for Recorder {
+    async fn notify_player(
+        &self,
+        player_id: PlayerId,
+        notification: SequencedNotification,
+    ) {
+        self.sent.lock().unwrap().push((player_id, notification));
+    }
+}
+
+/// Match results kept in memory.
+#[derive(Default)]
+struct Results {
+    saved: Mutex<Vec<MatchResult>>,
+}
+
+#[async_trait]
+impl MatchResultRepository for Results {
+    async fn save(
+        &self,
+        result: MatchResult,
+    ) {
+        self.saved.lock().unwrap().push(result);
+    }
+
+    async fn get(
+        &self,
+        game_id: GameId,
+    ) -> Option<MatchResult> {
+        self.saved.lock().unwrap().iter().find(|r| r.game_id == game_id).cloned()
+    }
+
+    async fn list_for_player(
+        &self,
+        player_id: PlayerId,
+    ) -> Vec<MatchResult> {
+        self.saved
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.players.contains(&player_id))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Every player is a guest.
+struct NoAccounts;
+
+#[async_trait]
+impl PlayerRepository for NoAccounts {
+    async fn insert(
+        &self,
+        _account: PlayerAccount,
+    ) -> Result<(), AccountError> {
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        _player_id: PlayerId,
+    ) -> Option<PlayerAccount> {
+        None
+    }
+
+    async fn find_by_username(
+        &self,
+        _username: &str,
+    ) -> Option<PlayerAccount> {
+        None
+    }
+}
+
+/// Players with a live connection.
+#[derive(Default)]
+struct Connections {
+    connected: Mutex<HashSet<PlayerId>>,
+}
+
+#[async_trait]
+impl Presence for Connections {
+    async fn is_connected(
+        &self,
+        player_id: PlayerId,
+    ) -> bool {
+        self.connected.lock().unwrap().contains(&player_id)
+    }
+}
+
+/// Games run on a `ManualClock`, so whole games play out as fast as the
+/// test advances time.
+struct Harness {
+    clock: Arc<ManualClock>,
+    notifier: Arc<Recorder>,
+    repository: Arc<dyn GameRepository>,
+    results: Arc<Results>,
+    game_store: Arc<GameRegistry>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        Self::with_repository(Arc::new(InMemoryGameRepository::new()))
+    }
+
+    fn with_repository(repository: Arc<dyn GameRepository>) -> Self {
+        Self::with_parts(repository, Arc::new(NoAccounts))
+    }
+
+    fn with_accounts(players: Arc<dyn PlayerRepository>) -> Self {
+        Self::with_parts(Arc::new(InMemoryGameRepository::new()), players)
+    }
+
+    fn with_parts(
+        repository: Arc<dyn GameRepository>,
+        players: Arc<dyn PlayerRepository>,
+    ) -> Self {
+        let clock = Arc::new(ManualClock::new());
+        let results = Arc::new(Results::default());
+        let game_store = Arc::new(GameRegistry::new(
+            clock.clone(),
+            Arc::clone(&repository),
+            results.clone(),
+            players,
+        ));
+        Self {
+            clock,
+            notifier: Arc::new(Recorder::default()),
+            repository,
+            results,
+            game_store,
+        }
+    }
+
+    async fn launch(
+        &self,
+        players: &[PlayerId],
+    ) -> GameId {
+        game_service::execute(
+            Arc::clone(&self.notifier),
+            Arc::clone(&self.game_store),
+            GameUseCase::LaunchGame {
+                players: players.to_vec(),
+                config: Box::new(config()),
+            },
+        )
+        .await
+        .unwrap();
+        settle().await;
+        let handles = self.game_store.handles().await;
+        assert_eq!(handles.len(), 1);
+        handles[0].game_id()
+    }
+
+    /// Moves the clock forward and lets every game catch up.
+    async fn advance(
+        &self,
+        by: Duration,
+    ) {
+        self.clock.advance(by);
+        settle().await;
+    }
+
+    fn sent_to(
+        &self,
+        player_id: PlayerId,
+    ) -> Vec<SequencedNotification> {
+        let sent = self.notifier.sent.lock().unwrap();
+        sent.iter()
+            .filter(|(to, _)| *to == player_id)
+            .map(|(_, n)| n.clone())
+            .collect()
+    }
+
+    fn sent(&self) -> usize {
+        self.notifier.sent.lock().unwrap().len()
+    }
+
+    fn lifecycle(
+        &self,
+        presence: Arc<Connections>,
+    ) -> GameLifecycle {
+        GameLifecycle::new(Arc::clone(&self.game_store), presence, self.clock.clone(), lifecycle_config())
+    }
+
+    async fn elapsed(
+        &self,
+        game_id: GameId,
+    ) -> Duration {
+        self.repository.get(game_id).await.unwrap().state.elapsed()
+    }
+}
+
+fn config() -> GameConfig {
+    GameConfig {
+        tick_interval: Duration::from_secs(1),
+        game_duration: Duration::from_secs(10),
+        countdown_duration: Duration::from_secs(3),
+        ..GameConfig::default()
+    }
+}
+
+fn lifecycle_config() -> LifecycleConfig {
+    LifecycleConfig {
+        sweep_interval: Duration::from_secs(1),
+        retention: Duration::from_secs(60),
+        abandon_after: Duration::from_secs(2),
+        restore_grace: Duration::from_secs(8),
+    }
+}
+
+/// Lets spawned tasks run until they have nothing left to do.
+async fn settle() {
+    for _ in 0..200 {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn test_full_game_runs_on_manual_clock() {
+    let t = Harness::new();
+    let players = [PlayerId::new(), PlayerId::new()];
+    let game_id = t.launch(&players).await;
+
+    // Nothing happens until time moves
+    let started = |t: &Harness| {
+        t.sent_to(players[0])
+            .iter()
+            .any(|n| matches!(n.notification, GameNotification::GameStarted { .. }))
+    };
+    assert!(!started(&t));
+    t.advance(config().countdown_duration).await;
+    assert!(started(&t));
+
+    // Ten ticks bring the game to its full duration, and it ends one interval later
+    for _ in 0..11 {
+        assert!(t.results.saved.lock().unwrap().is_empty());
+        t.advance(config().tick_interval).await;
+    }
+
+    let status = t.game_store.get(game_id).await.unwrap().status();
+    assert!(status.ended_at.is_some());
+    let snapshot = t.repository.get(game_id).await.unwrap();
+    assert_eq!(snapshot.state.phase(), &GamePhase::Ended);
+    assert!(snapshot.pending.is_empty());
+
+    let saved = t.results.saved.lock().unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].game_id, game_id);
+    assert_eq!(saved[0].duration_ms, 10_000);
+    // Stamped from the game's clock, not the host's
+    let ended_at = t.clock.wall_time().duration_since(UNIX_EPOCH).unwrap();
+    assert_eq!(saved[0].ended_at_ms, ended_at.as_millis() as u64);
+    drop(saved);
+
+    for player_id in players {
+        let sent = t.sent_to(player_id);
+        let seqs: Vec<u64> = sent.iter().map(|n| n.seq).collect();
+        assert_eq!(seqs, (1..=sent.len() as u64).collect::<Vec<_>>());
+        assert!(matches!(
+            sent.last().unwrap().notification,
+            GameNotification::GameEnded { .. }
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_launched_games_are_saved_with_their_timers() {
+    let t = Harness::new();
+    let launch = GameUseCase::LaunchGame {
+        players: vec![PlayerId::new(), PlayerId::new()],
+        config: Box::new(config()),
+    };
+    game_service::execute(Arc::clone(&t.notifier), Arc::clone(&t.game_store), launch)
+        .await
+        .unwrap();
+
+    // Saved before the game's task has had a chance to run
+    let game_id = t.game_store.handles().await[0].game_id();
+    let snapshot = t.repository.get(game_id).await.unwrap();
+    assert!(snapshot.pending.iter().any(|p| matches!(p.action, GameAction::Start)));
+
+    // The task runs each of them once
+    t.advance(config().countdown_duration).await;
+    let sent = t.notifier.sent.lock().unwrap();
+    let started = sent
+        .iter()
+        .filter(|(_, n)| matches!(n.notification, GameNotification::GameStarted { .. }))
+        .count();
+    assert_eq!(started, 2);
+}
+
+#[tokio::test]
+async fn test_ticks_do_not_drift_when_time_jumps() {
+    let t = Harness::new();
+    let game_id = t.launch(&[PlayerId::new(), PlayerId::new()]).await;
+    t.advance(config().countdown_duration).await;
+    let start = t.elapsed(game_id).await;
+
+    // Each tick is due one interval after the last one was due, not after it
+    // ran, so jumping several intervals at once runs every tick in between
+    t.advance(config().tick_interval * 4).await;
+    assert_eq!(t.elapsed(game_id).await, start + config().tick_interval * 4);
+
+    // Landing part way between ticks runs only those already due
+    t.advance(config().tick_interval / 2).await;
+    assert_eq!(t.elapsed(game_id).await, start + config().tick_interval * 4);
+    t.advance(config().tick_interval / 2).await;
+    assert_eq!(t.elapsed(game_id).await, start + config().tick_interval * 5);
+}
+
+#[tokio::test]
+async fn test_abort_cancels_pending_timers() {
+    let t = Harness::new();
+    let game_id = t.launch(&[PlayerId::new(), PlayerId::new()]).await;
+    t.advance(config().countdown_duration).await;
+    assert!(!t.repository.get(game_id).await.unwrap().pending.is_empty());
+
+    assert!(t.game_store.abort(game_id).await);
+    settle().await;
+    assert!(t.game_store.get(game_id).await.is_none());
+    assert!(t.repository.get(game_id).await.is_none());
+
+    let sent = t.sent();
+    t.advance(config().game_duration).await;
+    assert_eq!(t.sent(), sent);
+    assert!(t.results.saved.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_panicking_games_are_aborted_and_swept() {
+    let players = [PlayerId::new(), PlayerId::new()];
+    let before = Harness::new();
+    let game_id = before.launch(&players).await;
+    before.advance(config().countdown_duration + config().tick_interval).await;
+
+    // A tick interval no clock can add to makes the next tick panic
+    let mut snapshot = before.repository.get(game_id).await.unwrap();
+    let mut state = serde_json::to_value(&snapshot.state).unwrap();
+    state["tick_interval"] = serde_json::json!({ "secs": u64::MAX, "nanos": 0 });
+    snapshot.state = serde_json::from_value(state).unwrap();
+    let repository = Arc::new(InMemoryGameRepository::new());
+    repository.insert(game_id, snapshot).await;
+    let after = Harness::with_repository(repository);
+    game_service::restore_games(Arc::clone(&after.notifier), &after.game_store).await;
+
+    after.advance(config().tick_interval).await;
+    for player_id in players {
+        assert!(matches!(
+            after.sent_to(player_id).last().unwrap().notification,
+            GameNotification::GameAborted { .. }
+        ));
+    }
+    let handle = after.game_store.get(game_id).await.unwrap();
+    assert!(!handle.is_running());
+
+    let sent = after.sent();
+    after.advance(config().game_duration).await;
+    assert_eq!(after.sent(), sent);
+
+    let presence = Arc::new(Connections::default());
+    presence.connected.lock().unwrap().extend(players);
+    after.lifecycle(presence).sweep().await;
+    assert!(after.game_store.get(game_id).await.is_none());
+    assert!(after.repository.get(game_id).await.is_none());
+    assert!(after.results.saved.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_ended_games_are_evicted_after_retention() {
+    let t = Harness::new();
+    let players = [PlayerId::new(), PlayerId::new()];
+    let game_id = t.launch(&players).await;
+    let presence = Arc::new(Connections::default());
+    presence.connected.lock().unwrap().extend(players);
+    let mut lifecycle = t.lifecycle(presence);
+
+    t.advance(config().countdown_duration + config().game_duration + config().tick_interval)
+        .await;
+    assert!(t.game_store.get(game_id).await.unwrap().status().ended_at.is_some());
+
+    t.advance(lifecycle_config().retention - Duration::from_secs(1)).await;
+    lifecycle.sweep().await;
+    assert_eq!(t.game_store.stats().await.retained, 1);
+
+    t.advance(Duration::from_secs(1)).await;
+    lifecycle.sweep().await;
+    assert!(t.game_store.get(game_id).await.is_none());
+    assert!(t.repository.get(game_id).await.is_none());
+    // The result outlives the game
+    assert_eq!(t.results.saved.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_unattended_games_are_aborted() {
+    let t = Harness::new();
+    let players = [PlayerId::new(), PlayerId::new()];
+    let game_id = t.launch(&players).await;
+    let presence = Arc::new(Connections::default());
+    let mut lifecycle = t.lifecycle(Arc::clone(&presence));
+
+    // Abandonment is measured from when the game was first seen unattended
+    lifecycle.sweep().await;
+    t.advance(lifecycle_config().abandon_after - Duration::from_secs(1)).await;
+    lifecycle.sweep().await;
+    assert!(t.game_store.get(game_id).await.is_some());
+
+    // Anyone coming back resets it
+    presence.connected.lock().unwrap().insert(players[1]);
+    t.advance(Duration::from_secs(1)).await;
+    lifecycle.sweep().await;
+    presence.connected.lock().unwrap().clear();
+    lifecycle.sweep().await;
+    t.advance(lifecycle_config().abandon_after - Duration::from_secs(1)).await;
+    lifecycle.sweep().await;
+    assert!(t.game_store.get(game_id).await.is_some());
+
+    t.advance(Duration::from_secs(1)).await;
+    lifecycle.sweep().await;
+    settle().await;
+    assert!(t.game_store.get(game_id).await.is_none());
+    assert!(t.repository.get(game_id).await.is_none());
+
+    let sent = t.sent();
+    t.advance(config().game_duration).await;
+    assert_eq!(t.sent(), sent);
+}
+
+#[tokio::test]
+async fn test_attended_games_are_left_running() {
+    let t = Harness::new();
+    let players = [PlayerId::new(), PlayerId::new()];
+    let game_id = t.launch(&players).await;
+    let presence = Arc::new(Connections::default());
+    presence.connected.lock().unwrap().insert(players[0]);
+    let mut lifecycle = t.lifecycle(presence);
+
+    for _ in 0..10 {
+        t.advance(lifecycle_config().sweep_interval).await;
+        lifecycle.sweep().await;
+    }
+    let handle = t.game_store.get(game_id).await.unwrap();
+    assert!(handle.is_running());
+    assert!(handle.status().ended_at.is_none());
+}
+
+#[tokio::test]
+async fn test_restored_games_pick_up_their_timers() {
+    let dir = TempDir::new();
+    let players = [PlayerId::new(), PlayerId::new()];
+    let snapshots = Arc::new(FileGameRepository::open(dir.path()).await.unwrap());
+    let before = Harness::with_repository(snapshots.clone());
+    let game_id = before.launch(&players).await;
+    before.advance(config().countdown_duration + config().tick_interval * 2).await;
+    snapshots.flush().await.unwrap();
+    let last_seq = before.sent_to(players[0]).last().unwrap().seq;
+
+    // A restart: a fresh process opens the same directory on its own clock
+    let after = Harness::with_repository(Arc::new(FileGameRepository::open(dir.path()).await.unwrap()));
+    let restored = game_service::restore_games(Arc::clone(&after.notifier), &after.game_store).await;
+    assert_eq!(restored, 1);
+    settle().await;
+    assert_eq!(after.elapsed(game_id).await, config().tick_interval * 2);
+
+    // The next tick is due a full interval after the last one, not straight away
+    after.advance(config().tick_interval / 2).await;
+    assert_eq!(after.elapsed(game_id).await, config().tick_interval * 2);
+    after.advance(config().tick_interval / 2).await;
+    assert_eq!(after.elapsed(game_id).await, config().tick_interval * 3);
+
+    // Each player's stream carries on from where it stopped
+    let sent = after.sent_to(players[0]);
+    assert_eq!(sent[0].seq, last_seq + 1);
+
+    after.advance(config().game_duration).await;
+    let sent = after.sent_to(players[0]);
+    assert!(matches!(
+        sent.last().unwrap().notification,
+        GameNotification::GameEnded { .. }
+    ));
+    assert_eq!(after.results.saved.lock().unwrap()[0].duration_ms, 10_000);
+}
+
+#[tokio::test]
+async fn test_restored_games_wait_for_players_to_reconnect() {
+    let players = [PlayerId::new(), PlayerId::new()];
+    let before = Harness::new();
+    let game_id = before.launch(&players).await;
+    let snapshot = before.repository.get(game_id).await.unwrap();
+
+    let repository = Arc::new(InMemoryGameRepository::new());
+    repository.insert(game_id, snapshot).await;
+    let after = Harness::with_repository(repository);
+    game_service::restore_games(Arc::clone(&after.notifier), &after.game_store).await;
+    let mut lifecycle = after.lifecycle(Arc::new(Connections::default()));
+
+    // Nobody is connected straight after a restart, which is not abandonment
+    while after.clock.elapsed() < lifecycle_config().restore_grace {
+        lifecycle.sweep().await;
+        after.advance(lifecycle_config().sweep_interval).await;
+    }
+    lifecycle.sweep().await;
+    assert!(after.game_store.get(game_id).await.is_some());
+
+    // Once the grace period is over, the usual rules apply
+    after.advance(lifecycle_config().abandon_after).await;
+    lifecycle.sweep().await;
+    assert!(after.game_store.get(game_id).await.is_none());
+    assert!(after.results.saved.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_reconnecting_players_resume_where_they_left_off() {
+    let t = Harness::new();
+    let players = [PlayerId::new(), PlayerId::new()];
+    let game_id = t.launch(&players).await;
+    let accounts = AccountService::new(Arc::new(NoAccounts), Arc::new(HmacCredentials::new("secret", TTL, TTL)));
+    let resume_token = accounts.resume_token(players[0]);
+    t.advance(config().countdown_duration + config().tick_interval * 2).await;
+    let last_seq = t.sent_to(players[0]).last().unwrap().seq;
+
+    // The token from before the drop brings the same player back
+    let player_id = accounts.authenticate_resume(&resume_token).unwrap();
+    assert_eq!(player_id, players[0]);
+    assert!(game_service::resume_player(&t.game_store, player_id).await);
+    settle().await;
+
+    let sent = t.sent_to(player_id);
+    let resumed = sent.last().unwrap();
+    assert_eq!(resumed.seq, last_seq + 1);
+    let GameNotification::GameResumed { view, .. } = &resumed.notification else {
+        panic!("expected game_resumed");
+    };
+    let state = t.repository.get(game_id).await.unwrap().state;
+    assert_eq!(
+        serde_json::to_value(view).unwrap(),
+        serde_json::to_value(state.player_view(player_id).unwrap()).unwrap()
+    );
+
+    // Later notifications carry on from the resumed one
+    t.advance(config().tick_interval).await;
+    let seqs: Vec<u64> = t.sent_to(player_id).iter().map(|n| n.seq).collect();
+    assert_eq!(seqs, (1..=seqs.len() as u64).collect::<Vec<_>>());
+    assert!(seqs.len() > sent.len());
+}
+
+#[tokio::test]
+async fn test_resync_resends_the_current_view() {
+    let t = Harness::new();
+    let players = [PlayerId::new(), PlayerId::new()];
+    let game_id = t.launch(&players).await;
+    t.advance(config().countdown_duration + config().tick_interval).await;
+    let last_seq = t.sent_to(players[0]).last().unwrap().seq;
+
+    let resync = GameUseCase::Resync {
+        game_id,
+        player_id: players[0],
+    };
+    game_service::execute(Arc::clone(&t.notifier), Arc::clone(&t.game_store), resync)
+        .await
+        .unwrap();
+    settle().await;
+
+    // The snapshot is the client's new starting point, and the stream carries on from it
+    let resumed = t.sent_to(players[0]).last().unwrap().clone();
+    assert!(matches!(resumed.notification, GameNotification::GameResumed { .. }));
+    assert_eq!(resumed.seq, last_seq + 1);
+    t.advance(config().tick_interval).await;
+    let after: Vec<u64> = t
+        .sent_to(players[0])
+        .iter()
+        .map(|n| n.seq)
+        .filter(|&seq| seq > resumed.seq)
+        .collect();
+    assert!(!after.is_empty());
+    assert_eq!(
+        after,
+        (resumed.seq + 1..=resumed.seq + after.len() as u64).collect::<Vec<_>>()
+    );
+
+    // Only the player who asked is sent one
+    assert!(
+        !t.sent_to(players[1])
+            .iter()
+            .any(|n| matches!(n.notification, GameNotification::GameResumed { .. }))
+    );
+}
+
+#[tokio::test]
+async fn test_display_names_reach_game_started() {
+    let dir = TempDir::new();
+    let players = Arc::new(FilePlayerRepository::open(dir.path().join("players.json")).await.unwrap());
+    let accounts = AccountService::new(players.clone(), Arc::new(HmacCredentials::new("secret", TTL, TTL)));
+    let alice = accounts.register("alice", "correct horse", Some("Alice")).await.unwrap();
+    let guest = PlayerId::new();
+
+    let t = Harness::with_accounts(players);
+    t.launch(&[alice.player_id, guest]).await;
+    t.advance(config().countdown_duration).await;
+
+    for player_id in [alice.player_id, guest] {
+        let sent = t.sent_to(player_id);
+        let display_names = sent
+            .iter()
+            .find_map(|n| match &n.notification {
+                GameNotification::GameStarted { display_names, .. } => Some(display_names),
+                _ => None,
+            })
+            .unwrap();
+        // Guests have no name to show
+        assert_eq!(display_names, &HashMap::from([(alice.player_id, "Alice".to_string())]));
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use adapters::{FileGameRepository, FileMatchResultRepository, InMemoryGameRepository};
+use application::ports::out_::{
+    GameRepository, GameServiceError, GameSnapshot, MatchResult, MatchResultRepository, PendingAction,
+};
+use domain::{GameAction, GameConfig, GameId, GameState, PlayerId};
+
+mod common;
+use common::TempDir;
+
+fn snapshot(players: &[PlayerId]) -> GameSnapshot {
+    let (state, _) = GameState::launch(players.to_vec(), GameConfig::default());
+    GameSnapshot {
+        state,
+        pending: vec![PendingAction {
+            due_in: Duration::from_secs(3),
+            action: GameAction::Start,
+        }],
+        sequences: players.iter().map(|&p| (p, 4)).collect(),
+    }
+}
+
+#[tokio::test]
+async fn test_in_memory_update_requires_insert() {
+    let games = InMemoryGameRepository::new();
+    let game_id = GameId::new();
+    let result = games.update(game_id, snapshot(&[PlayerId::new()])).await;
+    assert!(matches!(result, Err(GameServiceError::GameNotFound(id)) if id == game_id));
+
+    games.insert(game_id, snapshot(&[PlayerId::new()])).await;
+    assert!(games.update(game_id, snapshot(&[PlayerId::new()])).await.is_ok());
+    assert_eq!(games.list().await, vec![game_id]);
+    assert!(games.remove(game_id).await.is_some());
+    assert!(games.get(game_id).await.is_none());
+}
+
+#[tokio::test]
+async fn test_file_snapshots_survive_reopening() {
+    let dir = TempDir::new();
+    let players = [PlayerId::new(), PlayerId::new()];
+    let game_id = GameId::new();
+
+    let games = FileGameRepository::open(dir.path()).await.unwrap();
+    games.insert(game_id, snapshot(&[PlayerId::new()])).await;
+    games.update(game_id, snapshot(&players)).await.unwrap();
+
+    // Nothing reaches the disk until a flush
+    assert!(FileGameRepository::open(dir.path()).await.unwrap().list().await.is_empty());
+    games.flush().await.unwrap();
+
+    let reopened = FileGameRepository::open(dir.path()).await.unwrap();
+    let restored = reopened.get(game_id).await.unwrap();
+    assert_eq!(restored.state.players(), players);
+    assert_eq!(restored.pending.len(), 1);
+    assert_eq!(restored.pending[0].due_in, Duration::from_secs(3));
+    assert_eq!(restored.sequences, players.iter().map(|&p| (p, 4)).collect::<HashMap<_, _>>());
+}
+
+#[tokio::test]
+async fn test_failed_flush_retries_only_what_failed() {
+    let dir = TempDir::new();
+    let (blocked, written) = (GameId::new(), GameId::new());
+
+    let games = FileGameRepository::open(dir.path()).await.unwrap();
+    games.insert(blocked, snapshot(&[PlayerId::new()])).await;
+    games.insert(written, snapshot(&[PlayerId::new()])).await;
+
+    // A directory where the snapshot is staged makes its write fail
+    let staging = dir.path().join(format!("{}.json.tmp", blocked.0));
+    std::fs::create_dir(&staging).unwrap();
+    assert!(games.flush().await.is_err());
+    let reopened = FileGameRepository::open(dir.path()).await.unwrap();
+    assert_eq!(reopened.list().await, vec![written]);
+
+    std::fs::remove_dir(&staging).unwrap();
+    games.flush().await.unwrap();
+    let reopened = FileGameRepository::open(dir.path()).await.unwrap();
+    assert!(reopened.get(blocked).await.is_some());
+    assert!(reopened.get(written).await.is_some());
+}
+
+#[tokio::test]
+async fn test_removed_games_leave_no_file() {
+    let dir = TempDir::new();
+    let (flushed, unflushed) = (GameId::new(), GameId::new());
+
+    let games = FileGameRepository::open(dir.path()).await.unwrap();
+    games.insert(flushed, snapshot(&[PlayerId::new()])).await;
+    games.flush().await.unwrap();
+    games.insert(unflushed, snapshot(&[PlayerId::new()])).await;
+
+    assert!(games.remove(flushed).await.is_some());
+    assert!(games.remove(unflushed).await.is_some());
+    games.flush().await.unwrap();
+
+    assert!(FileGameRepository::open(dir.path()).await.unwrap().list().await.is_empty());
+}
+
+fn match_result(
+    players: &[PlayerId],
+    ended_at_ms: u64,
+) -> MatchResult {
+    MatchResult {
+        game_id: GameId::new(),
+        players: players.to_vec(),
+        config: GameConfig::default(),
+        final_balances: players.iter().map(|&p| (p, 1000)).collect(),
+        duration_ms: 180_000,
+        trades: players.iter().map(|&p| (p, 3)).collect(),
+        ended_at_ms,
+    }
+}
+
+#[tokio::test]
+async fn test_match_results_survive_reopening() {
+    let dir = TempDir::new();
+    let (alice, bob) = (PlayerId::new(), PlayerId::new());
+    let (older, newer) = (match_result(&[alice, bob], 1_000), match_result(&[alice], 2_000));
+
+    let matches = FileMatchResultRepository::open(dir.path()).await.unwrap();
+    matches.save(older.clone()).await;
+    matches.save(newer.clone()).await;
+
+    // Nothing is left staged once a save returns
+    let staged = std::fs::read_dir(dir.path())
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().path().extension().is_some_and(|ext| ext == "tmp"))
+        .count();
+    assert_eq!(staged, 0);
+
+    let reopened = FileMatchResultRepository::open(dir.path()).await.unwrap();
+    let ids = |results: Vec<MatchResult>| results.iter().map(|r| r.game_id).collect::<Vec<_>>();
+    assert_eq!(ids(reopened.list_for_player(alice).await), vec![newer.game_id, older.game_id]);
+    assert_eq!(ids(reopened.list_for_player(bob).await), vec![older.game_id]);
+    assert_eq!(reopened.get(older.game_id).await.unwrap().trades, older.trades);
+}
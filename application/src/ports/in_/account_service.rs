@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::ports::out_::{AccountError, Credentials, PlayerAccount, PlayerRepository};
+use domain::PlayerId;
+
+const USERNAME_LENGTH: std::ops::RangeInclusive<usize> = 3..=24;
+const DISPLAY_NAME_LENGTH: std::ops::RangeInclusive<usize> = 1..=24;
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// What a client needs after signing in: its identity, and the token to
+/// present when it opens `/ws`.
+#[derive(Clone, Debug, Serialize)]
+pub struct Session {
+    pub player_id: PlayerId,
+    pub display_name: String,
+    pub token: String,
+}
+
+pub struct AccountService {
+    players: Arc<dyn PlayerRepository>,
+    credentials: Arc<dyn Credentials>,
+}
+
+impl AccountService {
+    pub fn new(
+        players: Arc<dyn PlayerRepository>,
+        credentials: Arc<dyn Credentials>,
+    ) -> Self {
+        Self { players, credentials }
+    }
+
+    /// Creates an account and signs it in. Usernames are case-insensitive;
+    /// the display name defaults to the username as typed.
+    pub async fn register(
+        &self,
+        username: &str,
+        password: &str,
+        display_name: Option<&str>,
+    ) -> Result<Session, AccountError> {
+        let username = username.trim();
+        if !USERNAME_LENGTH.contains(&username.chars().count())
+            || !username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(AccountError::InvalidUsername);
+        }
+        let display_name = display_name.map_or(username, str::trim);
+        if !DISPLAY_NAME_LENGTH.contains(&display_name.chars().count()) {
+            return Err(AccountError::InvalidDisplayName);
+        }
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(AccountError::WeakPassword);
+        }
+
+        let credentials = Arc::clone(&self.credentials);
+        let password = password.to_string();
+        let password_hash = blocking(move || credentials.hash_password(&password)).await;
+        let account = PlayerAccount {
+            player_id: PlayerId::new(),
+            username: username.to_ascii_lowercase(),
+            display_name: display_name.to_string(),
+            password_hash,
+        };
+        self.players.insert(account.clone()).await?;
+        Ok(self.session(account))
+    }
+
+    pub async fn login(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<Session, AccountError> {
+        let account = self
+            .players
+            .find_by_username(&username.trim().to_ascii_lowercase())
+            .await
+            .ok_or(AccountError::InvalidCredentials)?;
+        let credentials = Arc::clone(&self.credentials);
+        let (password, hash) = (password.to_string(), account.password_hash.clone());
+        if !blocking(move || credentials.verify_password(&password, &hash)).await {
+            return Err(AccountError::InvalidCredentials);
+        }
+        Ok(self.session(account))
+    }
+
+    /// The player behind a session token presented on connect.
+    pub fn authenticate(
+        &self,
+        token: &str,
+    ) -> Option<PlayerId> {
+        self.credentials.verify_token(token)
+    }
+
+    /// Lets a connection that drops be picked back up under the same id,
+    /// guests included. Resume tokens expire quickly and grant nothing else,
+    /// so a live connection is handed fresh ones as it goes.
+    pub fn resume_token(
+        &self,
+        player_id: PlayerId,
+    ) -> String {
+        self.credentials.issue_resume_token(player_id)
+    }
+
+    /// The player behind a resume token presented on reconnect.
+    pub fn authenticate_resume(
+        &self,
+        token: &str,
+    ) -> Option<PlayerId> {
+        self.credentials.verify_resume_token(token)
+    }
+
+    fn session(
+        &self,
+        account: PlayerAccount,
+    ) -> Session {
+        Session {
+            player_id: account.player_id,
+            token: self.credentials.issue_token(account.player_id),
+            display_name: account.display_name,
+        }
+    }
+}
+
+/// Runs password hashing, which is deliberately slow, off the async workers.
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> T {
+    match tokio::task::spawn_blocking(work).await {
+        Ok(result) => result,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
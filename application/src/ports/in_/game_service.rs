@@ -1,23 +1,212 @@
 use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
-use tokio::sync::RwLock;
+use serde::Serialize;
+use tokio::sync::{RwLock, mpsc, oneshot, watch};
+use tracing::{error, info};
 
-use crate::ports::out_::{GameEventNotifier, GameNotification, GameServiceError};
-use domain::{GameAction, GameConfig, GameEffect, GameEvent, GameId, GameState, PlayerId};
+use super::scheduler::Scheduler;
+use crate::ports::out_::{
+    Clock, GameEventNotifier, GameNotification, GameRepository, GameServiceError, GameSnapshot, MatchResult,
+    MatchResultRepository, PendingAction, PlayerRepository, SequencedNotification,
+};
+use domain::{
+    GameAction, GameConfig, GameEffect, GameEvent, GameId, GamePhase, GameState, OfferId, OptionSpec, OrderSpec, PlayerId,
+    Side,
+};
 
-pub type GameStore = Arc<RwLock<HashMap<GameId, GameState>>>;
+/// How often a game that has changed writes a snapshot. Anything newer is
+/// lost if the process dies before the next one.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
+enum GameCommand {
+    Action {
+        action: GameAction,
+        reply: oneshot::Sender<Result<(), GameServiceError>>,
+    },
+    /// Sends the player everything they need to pick the game back up.
+    Resume { player_id: PlayerId },
+    /// Drops every pending timer and stops the game's task.
+    Abort,
+}
+
+/// What the rest of the service can see of a game without asking its task.
+#[derive(Clone, Debug)]
+pub struct GameStatus {
+    pub players: Vec<PlayerId>,
+    /// When the game ended, if it has.
+    pub ended_at: Option<Instant>,
+    /// When the game was brought back from a snapshot, if it was.
+    pub restored_at: Option<Instant>,
+}
+
+/// Cheap, cloneable way to reach the task that owns a game's `GameState`.
+#[derive(Clone)]
+pub struct GameHandle {
+    game_id: GameId,
+    sender: mpsc::UnboundedSender<GameCommand>,
+    status: watch::Receiver<GameStatus>,
+}
+
+impl GameHandle {
+    pub fn game_id(&self) -> GameId {
+        self.game_id
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Whether the game's task is still there to take commands. It only goes
+    /// away on its own if the game was aborted after an internal error.
+    pub fn is_running(&self) -> bool {
+        !self.sender.is_closed()
+    }
+
+    /// Queues `action` behind whatever the game is already processing and
+    /// waits for it to be applied.
+    pub async fn send(
+        &self,
+        action: GameAction,
+    ) -> Result<(), GameServiceError> {
+        let (reply, outcome) = oneshot::channel();
+        self.sender
+            .send(GameCommand::Action { action, reply })
+            .map_err(|_| GameServiceError::GameNotFound(self.game_id))?;
+        outcome.await.unwrap_or(Err(GameServiceError::GameNotFound(self.game_id)))
+    }
+
+    /// Queues a `GameResumed` snapshot for `player_id` behind whatever they
+    /// have already been sent.
+    pub fn resume(
+        &self,
+        player_id: PlayerId,
+    ) -> Result<(), GameServiceError> {
+        self.sender
+            .send(GameCommand::Resume { player_id })
+            .map_err(|_| GameServiceError::GameNotFound(self.game_id))
+    }
+}
+
+/// Running games by id. Only handles live here, so the lock is held just long
+/// enough to look one up; games never wait on each other. Their snapshots are
+/// kept in the `GameRepository`.
+pub struct GameRegistry {
+    games: RwLock<HashMap<GameId, GameHandle>>,
+    /// Time source for every game launched through this registry.
+    clock: Arc<dyn Clock>,
+    repository: Arc<dyn GameRepository>,
+    /// Where each game's result is saved when it ends.
+    match_results: Arc<dyn MatchResultRepository>,
+    /// Accounts, for the names players are shown to each other by.
+    players: Arc<dyn PlayerRepository>,
+}
+
+impl GameRegistry {
+    pub fn new(
+        clock: Arc<dyn Clock>,
+        repository: Arc<dyn GameRepository>,
+        match_results: Arc<dyn MatchResultRepository>,
+        players: Arc<dyn PlayerRepository>,
+    ) -> Self {
+        Self {
+            games: RwLock::new(HashMap::new()),
+            clock,
+            repository,
+            match_results,
+            players,
+        }
+    }
+
+    pub async fn get(
+        &self,
+        game_id: GameId,
+    ) -> Option<GameHandle> {
+        self.games.read().await.get(&game_id).cloned()
+    }
+
+    async fn insert(
+        &self,
+        handle: GameHandle,
+    ) {
+        self.games.write().await.insert(handle.game_id, handle);
+    }
+
+    /// Unregisters a game and stops it wherever it is, cancelling its timers.
+    /// Returns whether the game was running.
+    pub async fn abort(
+        &self,
+        game_id: GameId,
+    ) -> bool {
+        let Some(handle) = self.remove(game_id).await else {
+            return false;
+        };
+        handle.sender.send(GameCommand::Abort).is_ok()
+    }
+
+    /// Unregisters a game and drops its stored state. Its task exits once the
+    /// last handle is dropped.
+    pub async fn remove(
+        &self,
+        game_id: GameId,
+    ) -> Option<GameHandle> {
+        let handle = self.games.write().await.remove(&game_id);
+        self.repository.remove(game_id).await;
+        handle
+    }
+
+    /// The unfinished game `player_id` is playing in, if any.
+    pub async fn find_by_player(
+        &self,
+        player_id: PlayerId,
+    ) -> Option<GameHandle> {
+        self.games
+            .read()
+            .await
+            .values()
+            .find(|h| {
+                let status = h.status.borrow();
+                h.is_running() && status.ended_at.is_none() && status.players.contains(&player_id)
+            })
+            .cloned()
+    }
+
+    pub async fn handles(&self) -> Vec<GameHandle> {
+        self.games.read().await.values().cloned().collect()
+    }
+
+    pub async fn stats(&self) -> GameStats {
+        let games = self.games.read().await;
+        let retained = games.values().filter(|h| h.status.borrow().ended_at.is_some()).count();
+        GameStats {
+            live: games.len() - retained,
+            retained,
+        }
+    }
+}
+
+/// Registered games: those still being played, and finished ones kept
+/// around until their retention period runs out.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct GameStats {
+    pub live: usize,
+    pub retained: usize,
+}
 
 pub enum GameUseCase {
     PlaceBid {
         game_id: GameId,
         player_id: PlayerId,
         value: i32,
+        quantity: u32,
     },
     PlaceAsk {
         game_id: GameId,
         player_id: PlayerId,
         value: i32,
+        quantity: u32,
     },
     CancelBid {
         game_id: GameId,
@@ -29,15 +218,85 @@ pub enum GameUseCase {
         player_id: PlayerId,
         price: i32,
     },
+    AmendOrder {
+        game_id: GameId,
+        player_id: PlayerId,
+        side: Side,
+        price: i32,
+        new_price: i32,
+        new_quantity: u32,
+    },
+    CancelAll {
+        game_id: GameId,
+        player_id: PlayerId,
+        side: Option<Side>,
+    },
+    PlaceOco {
+        game_id: GameId,
+        player_id: PlayerId,
+        first: OrderSpec,
+        second: OrderSpec,
+    },
+    PlaceBracket {
+        game_id: GameId,
+        player_id: PlayerId,
+        entry_price: i32,
+        quantity: u32,
+        take_profit: i32,
+        stop_loss: i32,
+    },
+    Borrow {
+        game_id: GameId,
+        player_id: PlayerId,
+        amount: i32,
+    },
+    Repay {
+        game_id: GameId,
+        player_id: PlayerId,
+        amount: i32,
+    },
+    TradeIndex {
+        game_id: GameId,
+        player_id: PlayerId,
+        side: Side,
+        quantity: u32,
+    },
+    BuyOption {
+        game_id: GameId,
+        player_id: PlayerId,
+        spec: OptionSpec,
+    },
+    WriteOption {
+        game_id: GameId,
+        player_id: PlayerId,
+        spec: OptionSpec,
+    },
+    ProposeOffer {
+        game_id: GameId,
+        player_id: PlayerId,
+        buyer: PlayerId,
+        ticker: PlayerId,
+        quantity: u32,
+        price: i32,
+    },
+    RespondOffer {
+        game_id: GameId,
+        player_id: PlayerId,
+        offer_id: OfferId,
+        accept: bool,
+    },
+    /// Resends the player's view of the game, for a client that noticed a
+    /// gap in its notifications.
+    Resync { game_id: GameId, player_id: PlayerId },
     LaunchGame {
         players: Vec<PlayerId>,
-        config: GameConfig,
+        config: Box<GameConfig>,
     },
 }
 
 pub async fn execute<N: GameEventNotifier + 'static>(
     notifier: Arc<N>,
-    game_store: GameStore,
+    game_store: Arc<GameRegistry>,
     use_case: GameUseCase,
 ) -> Result<(), GameServiceError> {
     match use_case {
@@ -45,14 +304,15 @@ pub async fn execute<N: GameEventNotifier + 'static>(
             game_id,
             player_id,
             value,
+            quantity,
         } => {
             process_action(
-                notifier,
-                game_store,
+                &game_store,
                 game_id,
                 GameAction::Bid {
                     player_id,
                     bid_value: value,
+                    quantity,
                 },
             )
             .await
@@ -61,14 +321,15 @@ pub async fn execute<N: GameEventNotifier + 'static>(
             game_id,
             player_id,
             value,
+            quantity,
         } => {
             process_action(
-                notifier,
-                game_store,
+                &game_store,
                 game_id,
                 GameAction::Ask {
                     player_id,
                     ask_value: value,
+                    quantity,
                 },
             )
             .await
@@ -77,113 +338,700 @@ pub async fn execute<N: GameEventNotifier + 'static>(
             game_id,
             player_id,
             price,
-        } => process_action(notifier, game_store, game_id, GameAction::CancelBid { player_id, price }).await,
+        } => process_action(&game_store, game_id, GameAction::CancelBid { player_id, price }).await,
         GameUseCase::CancelAsk {
             game_id,
             player_id,
             price,
-        } => process_action(notifier, game_store, game_id, GameAction::CancelAsk { player_id, price }).await,
+        } => process_action(&game_store, game_id, GameAction::CancelAsk { player_id, price }).await,
+        GameUseCase::AmendOrder {
+            game_id,
+            player_id,
+            side,
+            price,
+            new_price,
+            new_quantity,
+        } => {
+            process_action(
+                &game_store,
+                game_id,
+                GameAction::AmendOrder {
+                    player_id,
+                    side,
+                    price,
+                    new_price,
+                    new_quantity,
+                },
+            )
+            .await
+        }
+        GameUseCase::CancelAll {
+            game_id,
+            player_id,
+            side,
+        } => process_action(&game_store, game_id, GameAction::CancelAll { player_id, side }).await,
+        GameUseCase::Borrow {
+            game_id,
+            player_id,
+            amount,
+        } => process_action(&game_store, game_id, GameAction::Borrow { player_id, amount }).await,
+        GameUseCase::Repay {
+            game_id,
+            player_id,
+            amount,
+        } => process_action(&game_store, game_id, GameAction::Repay { player_id, amount }).await,
+        GameUseCase::TradeIndex {
+            game_id,
+            player_id,
+            side,
+            quantity,
+        } => {
+            process_action(
+                &game_store,
+                game_id,
+                GameAction::TradeIndex {
+                    player_id,
+                    side,
+                    quantity,
+                },
+            )
+            .await
+        }
+        GameUseCase::BuyOption {
+            game_id,
+            player_id,
+            spec,
+        } => process_action(&game_store, game_id, GameAction::BuyOption { player_id, spec }).await,
+        GameUseCase::WriteOption {
+            game_id,
+            player_id,
+            spec,
+        } => process_action(&game_store, game_id, GameAction::WriteOption { player_id, spec }).await,
+        GameUseCase::ProposeOffer {
+            game_id,
+            player_id,
+            buyer,
+            ticker,
+            quantity,
+            price,
+        } => {
+            process_action(
+                &game_store,
+                game_id,
+                GameAction::ProposeOffer {
+                    seller: player_id,
+                    buyer,
+                    ticker,
+                    quantity,
+                    price,
+                },
+            )
+            .await
+        }
+        GameUseCase::RespondOffer {
+            game_id,
+            player_id,
+            offer_id,
+            accept,
+        } => {
+            process_action(
+                &game_store,
+                game_id,
+                GameAction::RespondOffer {
+                    player_id,
+                    offer_id,
+                    accept,
+                },
+            )
+            .await
+        }
+        GameUseCase::PlaceOco {
+            game_id,
+            player_id,
+            first,
+            second,
+        } => {
+            process_action(
+                &game_store,
+                game_id,
+                GameAction::PlaceOco {
+                    player_id,
+                    first,
+                    second,
+                },
+            )
+            .await
+        }
+        GameUseCase::PlaceBracket {
+            game_id,
+            player_id,
+            entry_price,
+            quantity,
+            take_profit,
+            stop_loss,
+        } => {
+            process_action(
+                &game_store,
+                game_id,
+                GameAction::PlaceBracket {
+                    player_id,
+                    entry_price,
+                    quantity,
+                    take_profit,
+                    stop_loss,
+                },
+            )
+            .await
+        }
+        GameUseCase::Resync { game_id, player_id } => {
+            let handle = game_store.get(game_id).await.ok_or(GameServiceError::GameNotFound(game_id))?;
+            handle.resume(player_id)
+        }
         GameUseCase::LaunchGame { players, config } => {
+            config.validate()?;
             let game_id = GameId::new();
-            let (game_state, effects) = GameState::launch(players, config);
+            let (state, effects) = GameState::launch(players, *config);
+            // The launch timers go into the first snapshot rather than through the
+            // task, so a game saved before its task has run still has them
+            let mut pending = Vec::new();
+            let mut notifications = Vec::new();
+            for effect in effects {
+                match effect {
+                    GameEffect::DelayedAction { delay, action } => pending.push(PendingAction { due_in: delay, action }),
+                    notification => notifications.push(notification),
+                }
+            }
+            let snapshot = GameSnapshot {
+                state,
+                pending,
+                sequences: HashMap::new(),
+            };
+            game_store.repository.insert(game_id, snapshot.clone()).await;
 
-            game_store.write().await.insert(game_id, game_state);
-            process_effects(notifier, game_store, game_id, effects);
+            let handle = spawn_game(notifier, &game_store, game_id, snapshot, notifications, None);
+            game_store.insert(handle).await;
             Ok(())
         }
     }
 }
 
-async fn process_action<N: GameEventNotifier + 'static>(
-    notifier: Arc<N>,
-    game_store: GameStore,
+async fn process_action(
+    game_store: &GameRegistry,
     game_id: GameId,
     action: GameAction,
 ) -> Result<(), GameServiceError> {
-    let effects = {
-        let mut store = game_store.write().await;
-        let Some(game_state) = store.get_mut(&game_id) else {
-            return Err(GameServiceError::GameNotFound(game_id));
-        };
-        game_state.process_action(action)?
-    };
+    let handle = game_store.get(game_id).await.ok_or(GameServiceError::GameNotFound(game_id))?;
+    handle.send(action).await
+}
 
-    process_effects(notifier, game_store, game_id, effects);
-    Ok(())
+/// Catches a reconnecting player up on the game they were playing, if any.
+/// Returns whether they had one to resume.
+pub async fn resume_player(
+    game_store: &GameRegistry,
+    player_id: PlayerId,
+) -> bool {
+    match game_store.find_by_player(player_id).await {
+        Some(handle) => handle.resume(player_id).is_ok(),
+        None => false,
+    }
 }
 
-fn process_effects<N: GameEventNotifier + 'static>(
+/// Brings back every unfinished game in the repository, resuming its timers
+/// from where its last snapshot left them. Games that had already ended are
+/// dropped. Returns how many games were restored.
+pub async fn restore_games<N: GameEventNotifier + 'static>(
     notifier: Arc<N>,
-    game_store: GameStore,
+    game_store: &GameRegistry,
+) -> usize {
+    let mut restored = 0;
+    for game_id in game_store.repository.list().await {
+        let Some(snapshot) = game_store.repository.get(game_id).await else {
+            continue;
+        };
+        if snapshot.state.phase() == &GamePhase::Ended {
+            game_store.repository.remove(game_id).await;
+            continue;
+        }
+        let restored_at = Some(game_store.clock.now());
+        let handle = spawn_game(Arc::clone(&notifier), game_store, game_id, snapshot, Vec::new(), restored_at);
+        game_store.insert(handle).await;
+        info!(game_id = ?game_id, "Restored game");
+        restored += 1;
+    }
+    restored
+}
+
+/// Starts the task that owns the game in `snapshot`, beginning with `effects`.
+fn spawn_game<N: GameEventNotifier + 'static>(
+    notifier: Arc<N>,
+    game_store: &GameRegistry,
     game_id: GameId,
+    snapshot: GameSnapshot,
     effects: Vec<GameEffect>,
-) {
-    for effect in effects {
-        match effect {
-            GameEffect::Notification { player_id, event } => {
-                let notification = match event {
-                    GameEvent::Countdown(remaining) => GameNotification::Countdown { game_id, remaining },
-                    GameEvent::GameStarted {
-                        starting_price,
-                        starting_balance,
-                        players,
-                        game_duration_secs,
-                    } => GameNotification::GameStarted {
-                        game_id,
-                        starting_price,
-                        starting_balance,
-                        players,
-                        game_duration_secs,
-                    },
-                    GameEvent::PriceChanged { player_id, price } => GameNotification::PriceChanged {
-                        game_id,
-                        player_id,
-                        price,
-                    },
-                    GameEvent::BidPlaced { player_id, bid_value } => GameNotification::BidPlaced {
-                        game_id,
-                        player_id,
-                        bid_value,
-                    },
-                    GameEvent::AskPlaced { player_id, ask_value } => GameNotification::AskPlaced {
-                        game_id,
-                        player_id,
-                        ask_value,
-                    },
-                    GameEvent::BidFilled { player_id, bid_value } => GameNotification::BidFilled {
-                        game_id,
-                        player_id,
-                        bid_value,
-                    },
-                    GameEvent::AskFilled { player_id, ask_value } => GameNotification::AskFilled {
-                        game_id,
-                        player_id,
-                        ask_value,
-                    },
-                    GameEvent::BidCanceled { player_id, price } => GameNotification::BidCanceled {
-                        game_id,
-                        player_id,
-                        price,
-                    },
-                    GameEvent::AskCanceled { player_id, price } => GameNotification::AskCanceled {
-                        game_id,
-                        player_id,
-                        price,
-                    },
-                    GameEvent::GameEnded { final_balances } => GameNotification::GameEnded { game_id, final_balances },
-                };
-                let notifier = Arc::clone(&notifier);
-                tokio::spawn(async move {
-                    notifier.notify_player(player_id, notification).await;
-                });
+    restored_at: Option<Instant>,
+) -> GameHandle {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let (status, status_receiver) = watch::channel(GameStatus {
+        players: snapshot.state.players().to_vec(),
+        ended_at: None,
+        restored_at,
+    });
+    let clock = Arc::clone(&game_store.clock);
+    let mut scheduler = Scheduler::default();
+    let now = clock.now();
+    for pending in snapshot.pending {
+        scheduler.schedule(now + pending.due_in, pending.action);
+    }
+    let actor = GameActor {
+        game_id,
+        game_state: snapshot.state,
+        notifier,
+        clock,
+        repository: Arc::clone(&game_store.repository),
+        match_results: Arc::clone(&game_store.match_results),
+        players: Arc::clone(&game_store.players),
+        display_names: HashMap::new(),
+        scheduler,
+        receiver,
+        sequences: snapshot.sequences,
+        status,
+        dirty: false,
+    };
+    tokio::spawn(actor.run(effects));
+
+    GameHandle {
+        game_id,
+        sender,
+        status: status_receiver,
+    }
+}
+
+/// The task behind a `GameHandle`. Player actions and the game's own timers
+/// are applied one at a time, so `GameState` needs no lock.
+struct GameActor<N> {
+    game_id: GameId,
+    game_state: GameState,
+    notifier: Arc<N>,
+    clock: Arc<dyn Clock>,
+    repository: Arc<dyn GameRepository>,
+    match_results: Arc<dyn MatchResultRepository>,
+    players: Arc<dyn PlayerRepository>,
+    /// Display names of the players with an account, looked up once on start.
+    display_names: HashMap<PlayerId, String>,
+    scheduler: Scheduler,
+    receiver: mpsc::UnboundedReceiver<GameCommand>,
+    /// Last sequence number sent to each player.
+    sequences: HashMap<PlayerId, u64>,
+    status: watch::Sender<GameStatus>,
+    /// Whether anything has changed since the last snapshot.
+    dirty: bool,
+}
+
+impl<N: GameEventNotifier + 'static> GameActor<N> {
+    async fn run(
+        mut self,
+        launch_effects: Vec<GameEffect>,
+    ) {
+        for &player_id in self.game_state.players() {
+            if let Some(account) = self.players.get(player_id).await {
+                self.display_names.insert(player_id, account.display_name);
             }
-            GameEffect::DelayedAction { delay, action } => {
-                let notifier = Arc::clone(&notifier);
-                let game_store = Arc::clone(&game_store);
-                tokio::spawn(async move {
-                    tokio::time::sleep(delay).await;
-                    let _ = process_action(notifier, game_store, game_id, action).await;
-                });
+        }
+
+        let now = self.clock.now();
+        self.apply_effects(now, launch_effects).await;
+        self.persist(now).await;
+        let mut next_snapshot = now + SNAPSHOT_INTERVAL;
+        loop {
+            let next_due = self.scheduler.next_due();
+            // Timers already due run before the snapshot, so it never lags
+            // behind a jump of the clock
+            tokio::select! {
+                biased;
+                command = self.receiver.recv() => match command {
+                    Some(GameCommand::Action { action, reply }) => {
+                        let now = self.clock.now();
+                        let outcome = self.apply(now, action).await;
+                        let aborted = matches!(outcome, Err(GameServiceError::GameAborted(_)));
+                        let _ = reply.send(outcome);
+                        if aborted {
+                            break;
+                        }
+                    }
+                    Some(GameCommand::Resume { player_id }) => self.resume(player_id).await,
+                    // Unregistered, either explicitly or by dropping every handle
+                    Some(GameCommand::Abort) | None => break,
+                },
+                () = self.clock.sleep_until(next_due.unwrap_or(now)), if next_due.is_some() => {
+                    if let Some((due, action)) = self.scheduler.pop_due(self.clock.now())
+                        && let Err(GameServiceError::GameAborted(_)) = self.apply(due, action).await
+                    {
+                        break;
+                    }
+                }
+                () = self.clock.sleep_until(next_snapshot), if self.dirty => {
+                    let now = self.clock.now();
+                    self.persist(now).await;
+                    next_snapshot = now + SNAPSHOT_INTERVAL;
+                }
+            }
+        }
+        self.scheduler.cancel_all();
+    }
+
+    /// Applies `action` as of `now`, the instant any delays it schedules are
+    /// measured from.
+    async fn apply(
+        &mut self,
+        now: Instant,
+        action: GameAction,
+    ) -> Result<(), GameServiceError> {
+        let effects = self.process_action(action).await?;
+        self.apply_effects(now, effects).await;
+        // Nothing is left to fire once the game is over, and its final state
+        // is written straight away
+        if self.game_state.phase() == &GamePhase::Ended {
+            self.scheduler.cancel_all();
+            self.persist(now).await;
+        } else {
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Runs `action` through the domain, containing any panic to this game.
+    /// A game that panicked is left in an unknown state, so its players are
+    /// told it is over and the task stops. The lifecycle sweep then drops it
+    /// from the registry.
+    async fn process_action(
+        &mut self,
+        action: GameAction,
+    ) -> Result<Vec<GameEffect>, GameServiceError> {
+        let game_state = &mut self.game_state;
+        match panic::catch_unwind(AssertUnwindSafe(|| game_state.process_action(action))) {
+            Ok(result) => Ok(result?),
+            Err(_) => {
+                error!(game_id = ?self.game_id, action = ?action, "Game panicked, aborting it");
+                self.scheduler.cancel_all();
+                for player_id in self.game_state.players().to_vec() {
+                    let notification = GameNotification::GameAborted { game_id: self.game_id };
+                    self.notify(player_id, notification).await;
+                }
+                Err(GameServiceError::GameAborted(self.game_id))
+            }
+        }
+    }
+
+    async fn resume(
+        &mut self,
+        player_id: PlayerId,
+    ) {
+        let Some(view) = self.game_state.player_view(player_id) else {
+            return;
+        };
+        let config = self.game_state.config();
+        let notification = GameNotification::GameResumed {
+            game_id: self.game_id,
+            starting_balance: config.starting_balance,
+            display_names: self.display_names.clone(),
+            game_duration_secs: config.game_duration.as_secs(),
+            view,
+        };
+        self.notify(player_id, notification).await;
+        self.dirty = true;
+    }
+
+    /// Numbers `notification` within `player_id`'s stream and sends it.
+    async fn notify(
+        &mut self,
+        player_id: PlayerId,
+        notification: GameNotification,
+    ) {
+        let seq = self.sequences.entry(player_id).or_default();
+        *seq += 1;
+        let notification = SequencedNotification { seq: *seq, notification };
+        self.notifier.notify_player(player_id, notification).await;
+    }
+
+    /// Writes the game's state, timers and sequence numbers as of `now`.
+    async fn persist(
+        &mut self,
+        now: Instant,
+    ) {
+        let snapshot = GameSnapshot {
+            state: self.game_state.clone(),
+            pending: self.scheduler.pending(now),
+            sequences: self.sequences.clone(),
+        };
+        // Fails only once the game has been removed, which should not bring it back
+        let _ = self.repository.update(self.game_id, snapshot).await;
+        self.dirty = false;
+    }
+
+    /// Schedules delayed actions and hands notifications to the notifier one
+    /// at a time, numbering each player's stream as it goes.
+    async fn apply_effects(
+        &mut self,
+        now: Instant,
+        effects: Vec<GameEffect>,
+    ) {
+        let game_id = self.game_id;
+        for effect in effects {
+            match effect {
+                GameEffect::Notification { player_id, event } => {
+                    if let GameEvent::GameEnded {
+                        final_balances,
+                        settlements,
+                    } = &event
+                        && self.status.borrow().ended_at.is_none()
+                    {
+                        self.status.send_modify(|status| status.ended_at = Some(now));
+                        let ended_at_ms = self
+                            .clock
+                            .wall_time()
+                            .duration_since(UNIX_EPOCH)
+                            .map_or(0, |since| since.as_millis() as u64);
+                        self.match_results
+                            .save(MatchResult {
+                                game_id,
+                                players: self.game_state.players().to_vec(),
+                                config: self.game_state.config().clone(),
+                                final_balances: final_balances.clone(),
+                                duration_ms: self.game_state.elapsed().as_millis() as u64,
+                                trades: settlements.iter().map(|s| (s.player_id, s.trades)).collect(),
+                                ended_at_ms,
+                            })
+                            .await;
+                    }
+                    let notification = match event {
+                        GameEvent::Countdown(remaining) => GameNotification::Countdown { game_id, remaining },
+                        GameEvent::AuctionOpened { reference_price } => GameNotification::AuctionOpened {
+                            game_id,
+                            reference_price,
+                        },
+                        GameEvent::AuctionCleared { player_id, price } => GameNotification::AuctionCleared {
+                            game_id,
+                            player_id,
+                            price,
+                        },
+                        GameEvent::GameStarted {
+                            starting_price,
+                            starting_balance,
+                            players,
+                            game_duration_secs,
+                            tick_interval_ms,
+                        } => GameNotification::GameStarted {
+                            game_id,
+                            starting_price,
+                            starting_balance,
+                            players,
+                            display_names: self.display_names.clone(),
+                            game_duration_secs,
+                            tick_interval_ms,
+                        },
+                        GameEvent::PriceChanged { player_id, price } => GameNotification::PriceChanged {
+                            game_id,
+                            player_id,
+                            price,
+                        },
+                        GameEvent::RegimeChanged { regime } => GameNotification::RegimeChanged { game_id, regime },
+                        GameEvent::QuoteUpdated {
+                            player_id,
+                            quote,
+                            inventory,
+                        } => GameNotification::QuoteUpdated {
+                            game_id,
+                            player_id,
+                            quote,
+                            inventory,
+                        },
+                        GameEvent::TickIntervalChanged { interval_ms } => {
+                            GameNotification::TickIntervalChanged { game_id, interval_ms }
+                        }
+                        GameEvent::BidPlaced {
+                            player_id,
+                            bid_value,
+                            quantity,
+                        } => GameNotification::BidPlaced {
+                            game_id,
+                            player_id,
+                            bid_value,
+                            quantity,
+                        },
+                        GameEvent::AskPlaced {
+                            player_id,
+                            ask_value,
+                            quantity,
+                        } => GameNotification::AskPlaced {
+                            game_id,
+                            player_id,
+                            ask_value,
+                            quantity,
+                        },
+                        GameEvent::BidFilled {
+                            player_id,
+                            bid_value,
+                            quantity,
+                            remaining,
+                        } => GameNotification::BidFilled {
+                            game_id,
+                            player_id,
+                            bid_value,
+                            quantity,
+                            remaining,
+                        },
+                        GameEvent::AskFilled {
+                            player_id,
+                            ask_value,
+                            quantity,
+                            remaining,
+                        } => GameNotification::AskFilled {
+                            game_id,
+                            player_id,
+                            ask_value,
+                            quantity,
+                            remaining,
+                        },
+                        GameEvent::BidCanceled {
+                            player_id,
+                            price,
+                            quantity,
+                        } => GameNotification::BidCanceled {
+                            game_id,
+                            player_id,
+                            price,
+                            quantity,
+                        },
+                        GameEvent::AskCanceled {
+                            player_id,
+                            price,
+                            quantity,
+                        } => GameNotification::AskCanceled {
+                            game_id,
+                            player_id,
+                            price,
+                            quantity,
+                        },
+                        GameEvent::OrderAmended {
+                            player_id,
+                            side,
+                            price,
+                            new_price,
+                            new_quantity,
+                        } => GameNotification::OrderAmended {
+                            game_id,
+                            player_id,
+                            side,
+                            price,
+                            new_price,
+                            new_quantity,
+                        },
+                        GameEvent::OrdersCanceled { player_id, bids, asks } => GameNotification::OrdersCanceled {
+                            game_id,
+                            player_id,
+                            bids,
+                            asks,
+                        },
+                        GameEvent::OrderGroupUpdated {
+                            player_id,
+                            group_id,
+                            status,
+                        } => GameNotification::OrderGroupUpdated {
+                            game_id,
+                            player_id,
+                            group_id,
+                            status,
+                        },
+                        GameEvent::IndexTraded {
+                            player_id,
+                            side,
+                            quantity,
+                            price,
+                            position,
+                        } => GameNotification::IndexTraded {
+                            game_id,
+                            player_id,
+                            side,
+                            quantity,
+                            price,
+                            position,
+                        },
+                        GameEvent::OptionTraded {
+                            player_id,
+                            contract_id,
+                            spec,
+                            written,
+                            premium,
+                        } => GameNotification::OptionTraded {
+                            game_id,
+                            player_id,
+                            contract_id,
+                            spec,
+                            written,
+                            premium,
+                        },
+                        GameEvent::OptionSettled {
+                            player_id,
+                            contract_id,
+                            cash_delta,
+                        } => GameNotification::OptionSettled {
+                            game_id,
+                            player_id,
+                            contract_id,
+                            cash_delta,
+                        },
+                        GameEvent::OfferProposed { offer, expires_in_ms } => GameNotification::OfferProposed {
+                            game_id,
+                            offer,
+                            expires_in_ms,
+                        },
+                        GameEvent::OfferResolved {
+                            offer_id,
+                            seller,
+                            buyer,
+                            status,
+                        } => GameNotification::OfferResolved {
+                            game_id,
+                            offer_id,
+                            seller,
+                            buyer,
+                            status,
+                        },
+                        GameEvent::FinancingUpdated {
+                            player_id,
+                            cash_delta,
+                            debt,
+                        } => GameNotification::FinancingUpdated {
+                            game_id,
+                            player_id,
+                            cash_delta,
+                            debt,
+                        },
+                        GameEvent::InsiderTip {
+                            ticker,
+                            direction,
+                            in_ticks,
+                        } => GameNotification::InsiderTip {
+                            game_id,
+                            ticker,
+                            direction,
+                            in_ticks,
+                        },
+                        GameEvent::InsiderTipsRevealed { tips } => GameNotification::InsiderTipsRevealed { game_id, tips },
+                        GameEvent::GameEnded {
+                            final_balances,
+                            settlements,
+                        } => GameNotification::GameEnded {
+                            game_id,
+                            final_balances,
+                            settlements,
+                        },
+                    };
+                    self.notify(player_id, notification).await;
+                }
+                GameEffect::DelayedAction { delay, action } => {
+                    self.scheduler.schedule(now + delay, action);
+                }
             }
         }
     }
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tracing::info;
+
+use super::game_service::GameRegistry;
+use crate::ports::out_::{Clock, Presence};
+use domain::GameId;
+
+#[derive(Clone, Copy, Debug)]
+pub struct LifecycleConfig {
+    /// How often registered games are checked.
+    pub sweep_interval: Duration,
+    /// How long an ended game stays registered before it is evicted.
+    pub retention: Duration,
+    /// How long a running game may go without a single connected player
+    /// before it is aborted.
+    pub abandon_after: Duration,
+    /// How long a game restored after a restart is left alone before it can
+    /// count as abandoned, so its players have time to reconnect.
+    pub restore_grace: Duration,
+}
+
+impl Default for LifecycleConfig {
+    fn default() -> Self {
+        Self {
+            sweep_interval: Duration::from_secs(10),
+            retention: Duration::from_secs(300),
+            abandon_after: Duration::from_secs(30),
+            restore_grace: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Periodically evicts finished games and aborts abandoned ones so that the
+/// registry only ever holds games someone still cares about.
+pub struct GameLifecycle {
+    game_store: Arc<GameRegistry>,
+    presence: Arc<dyn Presence>,
+    clock: Arc<dyn Clock>,
+    config: LifecycleConfig,
+    /// When each running game was first seen with nobody connected.
+    unattended_since: HashMap<GameId, Instant>,
+}
+
+impl GameLifecycle {
+    pub fn new(
+        game_store: Arc<GameRegistry>,
+        presence: Arc<dyn Presence>,
+        clock: Arc<dyn Clock>,
+        config: LifecycleConfig,
+    ) -> Self {
+        Self {
+            game_store,
+            presence,
+            clock,
+            config,
+            unattended_since: HashMap::new(),
+        }
+    }
+
+    pub async fn run(mut self) {
+        loop {
+            let next_sweep = self.clock.now() + self.config.sweep_interval;
+            self.clock.sleep_until(next_sweep).await;
+            self.sweep().await;
+        }
+    }
+
+    /// Evicts ended games past their retention period, aborts running games
+    /// that have been unattended for too long, and drops games whose task
+    /// has stopped on an error.
+    pub async fn sweep(&mut self) {
+        let now = self.clock.now();
+        let handles = self.game_store.handles().await;
+        let registered: Vec<GameId> = handles.iter().map(|h| h.game_id()).collect();
+        self.unattended_since.retain(|game_id, _| registered.contains(game_id));
+
+        for handle in handles {
+            let game_id = handle.game_id();
+            let status = handle.status();
+
+            if !handle.is_running() && status.ended_at.is_none() {
+                self.unattended_since.remove(&game_id);
+                self.game_store.remove(game_id).await;
+                info!(game_id = ?game_id, "Removed failed game");
+                continue;
+            }
+
+            if let Some(ended_at) = status.ended_at {
+                self.unattended_since.remove(&game_id);
+                if now.saturating_duration_since(ended_at) < self.config.retention {
+                    continue;
+                }
+                self.game_store.remove(game_id).await;
+                info!(game_id = ?game_id, "Evicted ended game");
+                continue;
+            }
+
+            let mut attended = false;
+            for &player_id in &status.players {
+                if self.presence.is_connected(player_id).await {
+                    attended = true;
+                    break;
+                }
+            }
+            let recently_restored = status
+                .restored_at
+                .is_some_and(|at| now.saturating_duration_since(at) < self.config.restore_grace);
+            if attended || recently_restored {
+                self.unattended_since.remove(&game_id);
+                continue;
+            }
+
+            let since = *self.unattended_since.entry(game_id).or_insert(now);
+            if now.saturating_duration_since(since) >= self.config.abandon_after {
+                self.unattended_since.remove(&game_id);
+                self.game_store.abort(game_id).await;
+                info!(game_id = ?game_id, "Aborted abandoned game");
+            }
+        }
+    }
+}
@@ -1,4 +1,9 @@
+pub mod account_service;
 pub mod game_service;
+pub mod lifecycle;
 pub mod matchmaking_service;
+mod scheduler;
 
+pub use account_service::{AccountService, Session};
+pub use lifecycle::{GameLifecycle, LifecycleConfig};
 pub use matchmaking_service::{MatchmakingService, MatchmakingUseCase};
@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+use std::time::Instant;
+
+use domain::GameAction;
+
+use crate::ports::out_::PendingAction;
+
+/// Orders timers due at the same instant by when they were scheduled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct TimerKey {
+    due: Instant,
+    seq: u64,
+}
+
+/// A game's pending `GameEffect::DelayedAction`s, owned by the game's task.
+///
+/// Timers are kept as absolute deadlines. A timer scheduled while handling
+/// another is measured from that timer's deadline rather than from when the
+/// handling finished, so a chain of ticks does not drift.
+#[derive(Default)]
+pub(super) struct Scheduler {
+    timers: BTreeMap<TimerKey, GameAction>,
+    next_seq: u64,
+}
+
+impl Scheduler {
+    pub(super) fn schedule(
+        &mut self,
+        due: Instant,
+        action: GameAction,
+    ) {
+        self.next_seq += 1;
+        let key = TimerKey { due, seq: self.next_seq };
+        self.timers.insert(key, action);
+    }
+
+    pub(super) fn next_due(&self) -> Option<Instant> {
+        self.timers.keys().next().map(|key| key.due)
+    }
+
+    /// Removes the earliest timer if it is due by `now`, returning its
+    /// deadline along with the action.
+    pub(super) fn pop_due(
+        &mut self,
+        now: Instant,
+    ) -> Option<(Instant, GameAction)> {
+        let entry = self.timers.first_entry().filter(|entry| entry.key().due <= now)?;
+        let due = entry.key().due;
+        Some((due, entry.remove()))
+    }
+
+    /// Every timer still to fire, earliest first, as a delay from `now`.
+    pub(super) fn pending(
+        &self,
+        now: Instant,
+    ) -> Vec<PendingAction> {
+        self.timers
+            .iter()
+            .map(|(key, &action)| PendingAction {
+                due_in: key.due.saturating_duration_since(now),
+                action,
+            })
+            .collect()
+    }
+
+    pub(super) fn cancel_all(&mut self) {
+        self.timers.clear();
+    }
+}
@@ -0,0 +1,20 @@
+use std::time::{Instant, SystemTime};
+
+use async_trait::async_trait;
+
+/// Where running games get their time from. Games only ever wait through
+/// this, so a virtual clock can run a whole game as fast as it is advanced.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    /// The calendar time matching `now()`, for timestamps kept past the
+    /// process, such as when a match ended.
+    fn wall_time(&self) -> SystemTime;
+
+    /// Resolves once `now()` has reached `deadline`.
+    async fn sleep_until(
+        &self,
+        deadline: Instant,
+    );
+}
@@ -0,0 +1,39 @@
+use domain::PlayerId;
+
+/// Password hashing and session tokens, so the application never handles
+/// key material or hash formats itself.
+pub trait Credentials: Send + Sync {
+    fn hash_password(
+        &self,
+        password: &str,
+    ) -> String;
+
+    fn verify_password(
+        &self,
+        password: &str,
+        hash: &str,
+    ) -> bool;
+
+    fn issue_token(
+        &self,
+        player_id: PlayerId,
+    ) -> String;
+
+    /// The player a token was issued to, provided it is genuine and unexpired.
+    fn verify_token(
+        &self,
+        token: &str,
+    ) -> Option<PlayerId>;
+
+    /// A short-lived token that only lets `player_id` pick a dropped
+    /// connection back up. It is not accepted as a session token.
+    fn issue_resume_token(
+        &self,
+        player_id: PlayerId,
+    ) -> String;
+
+    fn verify_resume_token(
+        &self,
+        token: &str,
+    ) -> Option<PlayerId>;
+}
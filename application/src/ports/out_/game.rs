@@ -1,12 +1,19 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use serde::Serialize;
 
-use domain::{GameError, GameId, PlayerId};
+use domain::{
+    ContractId, Direction, GameError, GameId, GroupId, GroupStatus, InsiderTip, OfferId, OfferStatus, OptionSpec, PlayerId,
+    PlayerSettlement, PlayerView, Quote, Regime, Side, TradeOffer,
+};
 
 #[derive(Debug)]
 pub enum GameServiceError {
     GameNotFound(GameId),
     GameError(GameError),
+    /// The game hit an internal error and was stopped.
+    GameAborted(GameId),
 }
 
 impl From<GameError> for GameServiceError {
@@ -22,59 +29,190 @@ pub enum GameNotification {
         game_id: GameId,
         remaining: u32,
     },
+    AuctionOpened {
+        game_id: GameId,
+        reference_price: i32,
+    },
+    AuctionCleared {
+        game_id: GameId,
+        player_id: PlayerId,
+        price: i32,
+    },
     GameStarted {
         game_id: GameId,
         starting_price: i32,
         starting_balance: i32,
         players: Vec<PlayerId>,
+        /// Names of the players who have an account; guests are left out.
+        display_names: HashMap<PlayerId, String>,
         game_duration_secs: u64,
+        tick_interval_ms: u64,
+    },
+    /// Sent on reconnecting to a game in progress, in place of everything
+    /// the player missed.
+    GameResumed {
+        game_id: GameId,
+        starting_balance: i32,
+        display_names: HashMap<PlayerId, String>,
+        game_duration_secs: u64,
+        #[serde(flatten)]
+        view: PlayerView,
     },
     PriceChanged {
         game_id: GameId,
         player_id: PlayerId,
         price: i32,
     },
+    RegimeChanged {
+        game_id: GameId,
+        regime: Regime,
+    },
+    QuoteUpdated {
+        game_id: GameId,
+        player_id: PlayerId,
+        quote: Quote,
+        inventory: i32,
+    },
+    TickIntervalChanged {
+        game_id: GameId,
+        interval_ms: u64,
+    },
     BidPlaced {
         game_id: GameId,
         player_id: PlayerId,
         bid_value: i32,
+        quantity: u32,
     },
     AskPlaced {
         game_id: GameId,
         player_id: PlayerId,
         ask_value: i32,
+        quantity: u32,
     },
     BidFilled {
         game_id: GameId,
         player_id: PlayerId,
         bid_value: i32,
+        quantity: u32,
+        remaining: u32,
     },
     AskFilled {
         game_id: GameId,
         player_id: PlayerId,
         ask_value: i32,
+        quantity: u32,
+        remaining: u32,
     },
     BidCanceled {
         game_id: GameId,
         player_id: PlayerId,
         price: i32,
+        quantity: u32,
     },
     AskCanceled {
         game_id: GameId,
         player_id: PlayerId,
         price: i32,
+        quantity: u32,
+    },
+    OrderAmended {
+        game_id: GameId,
+        player_id: PlayerId,
+        side: Side,
+        price: i32,
+        new_price: i32,
+        new_quantity: u32,
+    },
+    OrdersCanceled {
+        game_id: GameId,
+        player_id: PlayerId,
+        bids: Vec<(i32, u32)>,
+        asks: Vec<(i32, u32)>,
+    },
+    OrderGroupUpdated {
+        game_id: GameId,
+        player_id: PlayerId,
+        group_id: GroupId,
+        status: GroupStatus,
+    },
+    IndexTraded {
+        game_id: GameId,
+        player_id: PlayerId,
+        side: Side,
+        quantity: u32,
+        price: i32,
+        position: i32,
+    },
+    OptionTraded {
+        game_id: GameId,
+        player_id: PlayerId,
+        contract_id: ContractId,
+        spec: OptionSpec,
+        written: bool,
+        premium: i32,
+    },
+    OptionSettled {
+        game_id: GameId,
+        player_id: PlayerId,
+        contract_id: ContractId,
+        cash_delta: i32,
+    },
+    OfferProposed {
+        game_id: GameId,
+        offer: TradeOffer,
+        expires_in_ms: u64,
+    },
+    OfferResolved {
+        game_id: GameId,
+        offer_id: OfferId,
+        seller: PlayerId,
+        buyer: PlayerId,
+        status: OfferStatus,
+    },
+    FinancingUpdated {
+        game_id: GameId,
+        player_id: PlayerId,
+        cash_delta: i32,
+        debt: i32,
+    },
+    InsiderTip {
+        game_id: GameId,
+        ticker: PlayerId,
+        direction: Direction,
+        in_ticks: u32,
+    },
+    InsiderTipsRevealed {
+        game_id: GameId,
+        tips: Vec<InsiderTip>,
     },
     GameEnded {
         game_id: GameId,
         final_balances: Vec<(PlayerId, i32)>,
+        settlements: Vec<PlayerSettlement>,
+    },
+    /// The game stopped on an internal error and will not continue.
+    GameAborted {
+        game_id: GameId,
     },
 }
 
+/// A notification numbered within the stream one game sends one player.
+/// Sequence numbers start at 1 and increase by one per notification, so a
+/// client that sees a gap knows it missed something.
+#[derive(Clone, Serialize)]
+pub struct SequencedNotification {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub notification: GameNotification,
+}
+
+/// Implementations must deliver each player's notifications in the order
+/// `notify_player` is called for them.
 #[async_trait]
 pub trait GameEventNotifier: Send + Sync {
     async fn notify_player(
         &self,
         player_id: PlayerId,
-        notification: GameNotification,
+        notification: SequencedNotification,
     );
 }
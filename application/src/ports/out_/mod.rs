@@ -1,5 +1,17 @@
+mod clock;
+mod credentials;
 mod game;
+mod players;
+mod presence;
 mod queue;
+mod repository;
+mod results;
 
-pub use game::{GameEventNotifier, GameNotification, GameServiceError};
+pub use clock::Clock;
+pub use credentials::Credentials;
+pub use game::{GameEventNotifier, GameNotification, GameServiceError, SequencedNotification};
+pub use players::{AccountError, PlayerAccount, PlayerRepository};
+pub use presence::Presence;
 pub use queue::QueueNotifier;
+pub use repository::{GameRepository, GameSnapshot, PendingAction};
+pub use results::{MatchResult, MatchResultRepository};
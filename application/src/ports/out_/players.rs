@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use domain::PlayerId;
+
+/// A registered player. `password_hash` is whatever `Credentials` produced.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerAccount {
+    pub player_id: PlayerId,
+    /// Lowercase, and unique across accounts.
+    pub username: String,
+    /// What opponents see.
+    pub display_name: String,
+    pub password_hash: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AccountError {
+    UsernameTaken,
+    InvalidUsername,
+    InvalidDisplayName,
+    WeakPassword,
+    InvalidCredentials,
+    /// The account could not be saved.
+    Storage,
+}
+
+#[async_trait]
+pub trait PlayerRepository: Send + Sync {
+    /// Fails with `AccountError::UsernameTaken` if the username is in use, or
+    /// `AccountError::Storage` if the account could not be saved.
+    async fn insert(
+        &self,
+        account: PlayerAccount,
+    ) -> Result<(), AccountError>;
+
+    async fn get(
+        &self,
+        player_id: PlayerId,
+    ) -> Option<PlayerAccount>;
+
+    async fn find_by_username(
+        &self,
+        username: &str,
+    ) -> Option<PlayerAccount>;
+}
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use domain::PlayerId;
+
+/// Who currently has a live connection.
+#[async_trait]
+pub trait Presence: Send + Sync {
+    async fn is_connected(
+        &self,
+        player_id: PlayerId,
+    ) -> bool;
+}
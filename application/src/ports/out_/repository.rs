@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use domain::{GameAction, GameId, GameState, PlayerId};
+
+use super::GameServiceError;
+
+/// A game's timer, relative to when the snapshot was taken.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct PendingAction {
+    pub due_in: Duration,
+    pub action: GameAction,
+}
+
+/// Everything needed to pick a game up where it left off.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub state: GameState,
+    /// Timers still to fire, earliest first.
+    pub pending: Vec<PendingAction>,
+    /// Last sequence number sent to each player.
+    pub sequences: HashMap<PlayerId, u64>,
+}
+
+/// Where the latest snapshot of every registered game is kept. Each game's
+/// task writes through here after every action it applies, so an adapter
+/// that persists its contents can bring games back after a restart.
+#[async_trait]
+pub trait GameRepository: Send + Sync {
+    async fn get(
+        &self,
+        game_id: GameId,
+    ) -> Option<GameSnapshot>;
+
+    async fn insert(
+        &self,
+        game_id: GameId,
+        snapshot: GameSnapshot,
+    );
+
+    /// Replaces the snapshot of a game already in the repository.
+    async fn update(
+        &self,
+        game_id: GameId,
+        snapshot: GameSnapshot,
+    ) -> Result<(), GameServiceError>;
+
+    async fn list(&self) -> Vec<GameId>;
+
+    async fn remove(
+        &self,
+        game_id: GameId,
+    ) -> Option<GameSnapshot>;
+}
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use domain::{GameConfig, GameId, PlayerId};
+
+/// The record of a finished game, saved as soon as it ends.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MatchResult {
+    pub game_id: GameId,
+    pub players: Vec<PlayerId>,
+    pub config: GameConfig,
+    pub final_balances: Vec<(PlayerId, i32)>,
+    /// Game time actually played.
+    pub duration_ms: u64,
+    /// How many trades each player made.
+    pub trades: Vec<(PlayerId, u32)>,
+    /// Wall-clock end of the game, in milliseconds since the Unix epoch.
+    pub ended_at_ms: u64,
+}
+
+#[async_trait]
+pub trait MatchResultRepository: Send + Sync {
+    async fn save(
+        &self,
+        result: MatchResult,
+    );
+
+    async fn get(
+        &self,
+        game_id: GameId,
+    ) -> Option<MatchResult>;
+
+    /// Every match `player_id` played in, most recent first.
+    async fn list_for_player(
+        &self,
+        player_id: PlayerId,
+    ) -> Vec<MatchResult>;
+}
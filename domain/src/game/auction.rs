@@ -0,0 +1,73 @@
+use std::cmp::Ordering;
+
+use super::order::{Order, OrderKind};
+
+/// Uncrosses a call auction for one ticker.
+///
+/// Since only the ticker's owner trades it, the house makes up the other
+/// side: it absorbs up to `house_liquidity` shares of net demand at
+/// `reference` and above, and as much net supply at `reference` and below.
+/// The clearing price is the candidate that maximizes executed volume, then
+/// minimizes the leftover imbalance. Any remaining tie follows market pressure
+/// (highest price on excess demand, lowest on excess supply), falling back to
+/// the one closest to `reference`. Stops take no part in the auction.
+pub(super) fn clearing_price(
+    bids: &[Order],
+    asks: &[Order],
+    reference: i32,
+    house_liquidity: u32,
+) -> i32 {
+    let limits = |orders: &[Order]| -> Vec<(i32, i64)> {
+        orders
+            .iter()
+            .filter(|o| o.kind == OrderKind::Limit)
+            .map(|o| (o.price, i64::from(o.quantity)))
+            .collect()
+    };
+    let bids = limits(bids);
+    let asks = limits(asks);
+    let house = i64::from(house_liquidity);
+
+    let mut candidates: Vec<i32> = bids.iter().chain(&asks).map(|&(price, _)| price).collect();
+    candidates.push(reference);
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    // (price, volume, signed imbalance left over) wherever something executes
+    let books: Vec<(i32, i64, i64)> = candidates
+        .into_iter()
+        .map(|price| {
+            let demand: i64 = bids.iter().filter(|&&(p, _)| p >= price).map(|&(_, q)| q).sum();
+            let supply: i64 = asks.iter().filter(|&&(p, _)| p <= price).map(|&(_, q)| q).sum();
+            let net = demand - supply;
+            let absorbed = match net.cmp(&0) {
+                Ordering::Greater if price >= reference => net.min(house),
+                Ordering::Less if price <= reference => net.max(-house),
+                _ => 0,
+            };
+            (price, demand.min(supply) + absorbed.abs(), net - absorbed)
+        })
+        .filter(|&(_, volume, _)| volume > 0)
+        .collect();
+
+    let Some(best) = books.iter().map(|&(_, volume, imbalance)| (volume, -imbalance.abs())).max() else {
+        return reference;
+    };
+    let tied: Vec<(i32, i64)> = books
+        .into_iter()
+        .filter(|&(_, volume, imbalance)| (volume, -imbalance.abs()) == best)
+        .map(|(price, _, imbalance)| (price, imbalance))
+        .collect();
+
+    let pressure = |cmp: Ordering| tied.iter().all(|&(_, imbalance)| imbalance.cmp(&0) == cmp);
+    if pressure(Ordering::Greater) {
+        tied.iter().map(|&(price, _)| price).max().unwrap_or(reference)
+    } else if pressure(Ordering::Less) {
+        tied.iter().map(|&(price, _)| price).min().unwrap_or(reference)
+    } else {
+        tied.iter()
+            .map(|&(price, _)| price)
+            .min_by_key(|price| (price - reference).abs())
+            .unwrap_or(reference)
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Tick interval as a function of game progress.
+///
+/// Each step applies from `from_progress` (the fraction of
+/// `GameConfig::game_duration` already played, `0.0..=1.0`) until the next
+/// one begins. Before the first step, or without any, the game ticks at
+/// `GameConfig::tick_interval`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TickCurve {
+    pub steps: Vec<TickStep>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TickStep {
+    pub from_progress: f32,
+    pub interval: Duration,
+}
+
+impl TickCurve {
+    /// Speeds up to `final_interval` for the last `fraction` of the game.
+    pub fn accelerate_at_end(
+        fraction: f32,
+        final_interval: Duration,
+    ) -> Self {
+        Self {
+            steps: vec![TickStep {
+                from_progress: 1.0 - fraction,
+                interval: final_interval,
+            }],
+        }
+    }
+
+    pub fn interval_at(
+        &self,
+        progress: f32,
+        base: Duration,
+    ) -> Duration {
+        self.steps
+            .iter()
+            .filter(|s| s.from_progress <= progress)
+            .max_by(|a, b| a.from_progress.total_cmp(&b.from_progress))
+            .map_or(base, |s| s.interval)
+    }
+}
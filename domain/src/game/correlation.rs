@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// How strongly order activity on one ticker moves the others.
+///
+/// Tickers are addressed by seat, the index of their owner in the player list
+/// the game was created with. Every weight scales the notional value handed to
+/// the `Ticker::on_*` hooks, so `1.0` is the full impact and `0.0` none at all.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Correlation {
+    /// The same weight for every rival ticker.
+    Uniform { own: f32, rival: f32 },
+    /// Tickers grouped into sectors; `sectors[seat]` is the sector of that seat.
+    /// Seats missing from the list share a sector of their own.
+    Sectors {
+        sectors: Vec<usize>,
+        own: f32,
+        same_sector: f32,
+        other_sector: f32,
+    },
+    /// Full influence matrix, `weights[source][target]`. Pairs outside the
+    /// matrix have no influence.
+    Matrix(Vec<Vec<f32>>),
+}
+
+impl Default for Correlation {
+    /// Every order moves every ticker equally.
+    fn default() -> Self {
+        Correlation::Uniform { own: 1.0, rival: 1.0 }
+    }
+}
+
+impl Correlation {
+    /// Weight of activity on seat `source`'s ticker when applied to seat `target`'s.
+    pub fn weight(
+        &self,
+        source: usize,
+        target: usize,
+    ) -> f32 {
+        match self {
+            Correlation::Uniform { own, rival } => {
+                if source == target {
+                    *own
+                } else {
+                    *rival
+                }
+            }
+            Correlation::Sectors {
+                sectors,
+                own,
+                same_sector,
+                other_sector,
+            } => {
+                if source == target {
+                    *own
+                } else if sectors.get(source).is_some_and(|s| sectors.get(target) == Some(s)) {
+                    *same_sector
+                } else {
+                    *other_sector
+                }
+            }
+            Correlation::Matrix(weights) => weights.get(source).and_then(|row| row.get(target)).copied().unwrap_or(0.0),
+        }
+    }
+}
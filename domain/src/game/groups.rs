@@ -0,0 +1,223 @@
+use crate::PlayerId;
+
+use super::order::{BracketExits, Fill, GroupId, GroupStatus, Order, OrderGroup, OrderKind, OrderSpec, notional};
+use super::state::GameState;
+use super::ticker::Ticker;
+use super::{GameEffect, GameError, GameEvent, Side};
+
+impl GameState {
+    fn allocate_group_id(&mut self) -> GroupId {
+        self.next_group_id += 1;
+        GroupId(self.next_group_id)
+    }
+
+    fn apply_placement_impact(
+        &mut self,
+        player_id: PlayerId,
+        spec: OrderSpec,
+    ) {
+        let notional = notional(spec.price, spec.quantity) as f32;
+        let impact = match spec.side {
+            Side::Bid => Ticker::on_bid_placed,
+            Side::Ask => Ticker::on_ask_placed,
+        };
+        self.apply_impact(player_id, notional, impact);
+    }
+
+    pub(super) fn handle_place_oco(
+        &mut self,
+        player_id: PlayerId,
+        first: OrderSpec,
+        second: OrderSpec,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        self.require_trading("PlaceOco")?;
+        self.validate_order(player_id, first.price, first.quantity, 0)?;
+        self.validate_order(player_id, second.price, second.quantity, 1)?;
+
+        let legs = [first, second];
+        let state = self.players.get(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
+
+        // Legs on the same side share one reservation, sized for the larger of them
+        let cash_needed = legs
+            .iter()
+            .filter(|l| l.side == Side::Bid)
+            .map(|l| notional(l.price, l.quantity))
+            .max()
+            .unwrap_or(0);
+        if cash_needed > state.available_cash() {
+            return Err(GameError::InsufficientFunds {
+                available: state.available_cash(),
+                required: cash_needed,
+            });
+        }
+
+        let shares_needed = legs
+            .iter()
+            .filter(|l| l.side == Side::Ask)
+            .map(|l| l.quantity as usize)
+            .max()
+            .unwrap_or(0);
+        if shares_needed > state.available_shares() {
+            return Err(GameError::InsufficientShares {
+                available: state.available_shares(),
+                required: shares_needed,
+            });
+        }
+
+        let group_id = self.allocate_group_id();
+        if let Some(state) = self.players.get_mut(&player_id) {
+            state.groups.insert(group_id, OrderGroup::default());
+            for leg in legs {
+                let order = Order::from_spec(leg, group_id);
+                match leg.side {
+                    Side::Bid => state.open_bids.push(order),
+                    Side::Ask => state.open_asks.push(order),
+                }
+            }
+        }
+
+        // Stops stay hidden until they trigger
+        for leg in legs.into_iter().filter(|l| l.kind == OrderKind::Limit) {
+            self.apply_placement_impact(player_id, leg);
+        }
+
+        Ok(self.broadcast(vec![GameEvent::OrderGroupUpdated {
+            player_id,
+            group_id,
+            status: GroupStatus::Placed { legs: legs.to_vec() },
+        }]))
+    }
+
+    pub(super) fn handle_place_bracket(
+        &mut self,
+        player_id: PlayerId,
+        entry_price: i32,
+        quantity: u32,
+        take_profit: i32,
+        stop_loss: i32,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        self.require_trading("PlaceBracket")?;
+        // The entry is later swapped for two exits, so leave room for both
+        self.validate_order(player_id, entry_price, quantity, 1)?;
+
+        let current_price = self.player_tickers.get(&player_id).map(|pt| pt.current_price).unwrap_or(0);
+        self.config.order_rules.check_price(take_profit, current_price)?;
+        self.config.order_rules.check_price(stop_loss, current_price)?;
+        if stop_loss >= take_profit {
+            return Err(GameError::InvalidBracket { take_profit, stop_loss });
+        }
+
+        let entry = Order::new(entry_price, quantity);
+        let state = self.players.get(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
+        if entry.notional() > state.available_cash() {
+            return Err(GameError::InsufficientFunds {
+                available: state.available_cash(),
+                required: entry.notional(),
+            });
+        }
+
+        let group_id = self.allocate_group_id();
+        if let Some(state) = self.players.get_mut(&player_id) {
+            state.groups.insert(
+                group_id,
+                OrderGroup {
+                    pending_exits: Some(BracketExits {
+                        take_profit,
+                        stop_loss,
+                        quantity,
+                        filled: 0,
+                    }),
+                },
+            );
+            state.open_bids.push(Order {
+                group: Some(group_id),
+                ..entry
+            });
+        }
+
+        let legs = vec![
+            OrderSpec {
+                side: Side::Bid,
+                kind: OrderKind::Limit,
+                price: entry_price,
+                quantity,
+            },
+            OrderSpec {
+                side: Side::Ask,
+                kind: OrderKind::Limit,
+                price: take_profit,
+                quantity,
+            },
+            OrderSpec {
+                side: Side::Ask,
+                kind: OrderKind::Stop,
+                price: stop_loss,
+                quantity,
+            },
+        ];
+        self.apply_placement_impact(player_id, legs[0]);
+
+        Ok(self.broadcast(vec![GameEvent::OrderGroupUpdated {
+            player_id,
+            group_id,
+            status: GroupStatus::Placed { legs },
+        }]))
+    }
+
+    /// Applies a fill to the order group it belongs to: a completed bracket
+    /// entry arms its exits, any other grouped fill cancels the remaining legs.
+    pub(super) fn cascade_fill(
+        &mut self,
+        fill: &Fill,
+    ) -> Vec<GameEvent> {
+        let Some(group_id) = fill.group else {
+            return Vec::new();
+        };
+        let player_id = fill.player_id;
+        let Some(state) = self.players.get_mut(&player_id) else {
+            return Vec::new();
+        };
+        let Some(group) = state.groups.get_mut(&group_id) else {
+            return Vec::new();
+        };
+
+        if let Some(exits) = &mut group.pending_exits {
+            exits.filled += fill.quantity;
+            if fill.remaining > 0 {
+                return Vec::new();
+            }
+            let exits = *exits;
+            state.arm_exits(group_id, exits);
+            return vec![GameEvent::OrderGroupUpdated {
+                player_id,
+                group_id,
+                status: GroupStatus::Armed,
+            }];
+        }
+
+        state.groups.remove(&group_id);
+        let linked = |o: &mut Order| o.group == Some(group_id);
+        let canceled_bids: Vec<Order> = state.open_bids.extract_if(.., linked).collect();
+        let canceled_asks: Vec<Order> = state.open_asks.extract_if(.., linked).collect();
+
+        let mut events: Vec<GameEvent> = canceled_bids
+            .into_iter()
+            .map(|o| GameEvent::BidCanceled {
+                player_id,
+                price: o.price,
+                quantity: o.quantity,
+            })
+            .chain(canceled_asks.into_iter().map(|o| GameEvent::AskCanceled {
+                player_id,
+                price: o.price,
+                quantity: o.quantity,
+            }))
+            .collect();
+        events.push(GameEvent::OrderGroupUpdated {
+            player_id,
+            group_id,
+            status: GroupStatus::Triggered,
+        });
+        events
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use crate::PlayerId;
+
+use super::order::{notional, to_cash};
+use super::state::GameState;
+use super::{GameEffect, GameError, GameEvent, GamePhase, Side};
+
+/// The composite index traded alongside the player tickers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexConfig {
+    /// Weight of each seat's ticker. Seats without a weight count as `1`, so
+    /// an empty list gives an equal-weighted index.
+    pub weights: Vec<u32>,
+    /// Largest long or short position a player may hold.
+    pub max_position: u32,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            weights: Vec::new(),
+            max_position: 20,
+        }
+    }
+}
+
+impl GameState {
+    /// Recomputes the index as the weighted average of all player tickers.
+    pub(super) fn update_index_price(&mut self) {
+        let mut weighted = 0i64;
+        let mut total = 0i64;
+        for (seat, player_id) in self.seats.iter().enumerate() {
+            let Some(player_ticker) = self.player_tickers.get(player_id) else {
+                continue;
+            };
+            let weight = i64::from(self.config.index.weights.get(seat).copied().unwrap_or(1));
+            weighted += weight * i64::from(player_ticker.current_price);
+            total += weight;
+        }
+        if total > 0 {
+            self.index_price = (weighted / total) as i32;
+        }
+    }
+
+    /// Buys or sells the index at its current price. Index trades execute
+    /// immediately and may take the position short. A short's proceeds are
+    /// credited but held as margin until it is covered.
+    pub(super) fn handle_trade_index(
+        &mut self,
+        player_id: PlayerId,
+        side: Side,
+        quantity: u32,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        self.require_phase(GamePhase::Running, "TradeIndex")?;
+        self.config.order_rules.check_quantity(quantity)?;
+
+        let price = self.index_price;
+        let limit = i64::from(self.config.index.max_position);
+        let state = self.players.get_mut(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
+
+        // Widened before anything is signed, so no quantity can wrap into the other side
+        let cost = notional(price, quantity);
+        let (position, cash_delta) = match side {
+            Side::Bid => (i64::from(state.index_position) + i64::from(quantity), -cost),
+            Side::Ask => (i64::from(state.index_position) - i64::from(quantity), cost),
+        };
+        if position.abs() > limit {
+            return Err(GameError::IndexPositionLimit { position, limit });
+        }
+        let position = i32::try_from(position).map_err(|_| GameError::IndexPositionLimit { position, limit })?;
+        let short_before = (-i64::from(state.index_position)).max(0);
+        let short_after = (-i64::from(position)).max(0);
+        let margin = if short_after >= short_before {
+            i64::from(state.index_margin) + notional(price, (short_after - short_before) as u32)
+        } else {
+            // Covering releases the margin in proportion to what is bought back
+            i64::from(state.index_margin) * short_after / short_before
+        };
+        let available = state.available_cash() + (i64::from(state.index_margin) - margin).max(0);
+        if -cash_delta > available {
+            return Err(GameError::InsufficientFunds {
+                available,
+                required: -cash_delta,
+            });
+        }
+        let cash = to_cash(i64::from(state.cash) + cash_delta)?;
+        let margin = to_cash(margin)?;
+
+        state.cash = cash;
+        state.index_position = position;
+        state.index_margin = margin;
+        state.trades += 1;
+
+        Ok(self.broadcast(vec![GameEvent::IndexTraded {
+            player_id,
+            side,
+            quantity,
+            price,
+            position,
+        }]))
+    }
+}
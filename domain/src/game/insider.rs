@@ -0,0 +1,137 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::PlayerId;
+
+use super::state::GameState;
+use super::ticker::Decay;
+use super::{GameEffect, GameEvent};
+
+/// Occasional private hints about a force that will hit a ticker a few ticks
+/// later. Tips can be off on timing, and some describe forces that never come.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InsiderConfig {
+    /// Chance on each tick that some player is slipped a tip.
+    pub tip_chance: f64,
+    pub min_lead_ticks: u32,
+    pub max_lead_ticks: u32,
+    /// The lead a player is told may be off by up to this many ticks.
+    pub lead_noise_ticks: u32,
+    /// Chance a tip describes a force that never arrives.
+    pub false_tip_chance: f64,
+    /// Pressure of a tipped force, in units of base volatility.
+    pub force_pressure: f32,
+    /// How many ticks a tipped force lasts once it hits.
+    pub force_ticks: u32,
+}
+
+impl Default for InsiderConfig {
+    fn default() -> Self {
+        Self {
+            tip_chance: 0.05,
+            min_lead_ticks: 3,
+            max_lead_ticks: 8,
+            lead_noise_ticks: 1,
+            false_tip_chance: 0.25,
+            force_pressure: 0.5,
+            force_ticks: 4,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Bullish,
+    Bearish,
+}
+
+/// A tip as recorded for the post-game reveal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InsiderTip {
+    pub recipient: PlayerId,
+    pub ticker: PlayerId,
+    pub direction: Direction,
+    /// The lead the recipient was told.
+    pub reported_in_ticks: u32,
+    /// When the force really hit, or `None` for a false tip.
+    pub actual_in_ticks: Option<u32>,
+}
+
+/// A tipped force waiting to hit its ticker.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(super) struct ScheduledForce {
+    ticker: PlayerId,
+    pressure: f32,
+    ticks_left: u32,
+}
+
+impl GameState {
+    /// Counts down scheduled forces and applies those that are due this tick.
+    pub(super) fn apply_scheduled_forces(&mut self) {
+        let Some(config) = self.config.insider else {
+            return;
+        };
+        for force in &mut self.scheduled_forces {
+            force.ticks_left = force.ticks_left.saturating_sub(1);
+        }
+        for force in self.scheduled_forces.extract_if(.., |f| f.ticks_left == 0) {
+            if let Some(player_ticker) = self.player_tickers.get_mut(&force.ticker) {
+                player_ticker
+                    .ticker
+                    .add_force(force.pressure, 0.0, Decay::duration(config.force_ticks));
+            }
+        }
+    }
+
+    /// Rolls for a tip and, if one is due, notifies only its recipient.
+    pub(super) fn issue_insider_tip(&mut self) -> Vec<GameEffect> {
+        let Some(config) = self.config.insider else {
+            return Vec::new();
+        };
+        let mut rng = rand::thread_rng();
+        if !rng.gen_bool(config.tip_chance.clamp(0.0, 1.0)) {
+            return Vec::new();
+        }
+        let (Some(&recipient), Some(&ticker)) = (self.seats.choose(&mut rng), self.seats.choose(&mut rng)) else {
+            return Vec::new();
+        };
+
+        let direction = if rng.r#gen() { Direction::Bullish } else { Direction::Bearish };
+        let lead = rng
+            .gen_range(config.min_lead_ticks..=config.max_lead_ticks.max(config.min_lead_ticks))
+            .max(1);
+        let noise = config.lead_noise_ticks as i64;
+        let reported = (i64::from(lead) + rng.gen_range(-noise..=noise)).max(1) as u32;
+        let genuine = !rng.gen_bool(config.false_tip_chance.clamp(0.0, 1.0));
+
+        if genuine {
+            let pressure = match direction {
+                Direction::Bullish => config.force_pressure,
+                Direction::Bearish => -config.force_pressure,
+            };
+            self.scheduled_forces.push(ScheduledForce {
+                ticker,
+                pressure,
+                ticks_left: lead,
+            });
+        }
+        self.insider_tips.push(InsiderTip {
+            recipient,
+            ticker,
+            direction,
+            reported_in_ticks: reported,
+            actual_in_ticks: genuine.then_some(lead),
+        });
+
+        vec![GameEffect::Notification {
+            player_id: recipient,
+            event: GameEvent::InsiderTip {
+                ticker,
+                direction,
+                in_ticks: reported,
+            },
+        }]
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use crate::PlayerId;
+
+use super::order::notional;
+use super::state::{GameState, PlayerState};
+use super::{GameEffect, GameError, GameEvent};
+
+/// Per-tick interest on idle cash and on margin debt.
+///
+/// Rates are in basis points per tick. Fractions of a unit are carried over
+/// between ticks rather than rounded away, so small balances still accrue.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InterestModel {
+    pub cash_rate_bps: u32,
+    pub borrow_rate_bps: u32,
+    /// Largest allowed `(equity + debt) / equity`, as a percentage. `100`
+    /// forbids borrowing entirely.
+    pub max_leverage_pct: u32,
+}
+
+impl Default for InterestModel {
+    fn default() -> Self {
+        Self {
+            cash_rate_bps: 1,
+            borrow_rate_bps: 3,
+            max_leverage_pct: 200,
+        }
+    }
+}
+
+/// Margin debt and the fractional interest not yet booked.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub(super) struct Financing {
+    pub(super) debt: i32,
+    cash_carry: f64,
+    debt_carry: f64,
+}
+
+impl GameState {
+    /// Cash plus everything `player_id` holds at market, minus debt. Options
+    /// count at their intrinsic value, against the player when written.
+    fn equity(
+        &self,
+        player_id: PlayerId,
+        state: &PlayerState,
+    ) -> i64 {
+        let price = |ticker: PlayerId| self.player_tickers.get(&ticker).map_or(0, |pt| pt.current_price);
+        let shares = notional(price(player_id), state.shares.len() as u32);
+        let holdings: i64 = state
+            .holdings
+            .iter()
+            .map(|(&ticker, &qty)| notional(price(ticker), qty))
+            .sum();
+        let index = i64::from(state.index_position) * i64::from(self.index_price);
+        let options: i64 = state
+            .options
+            .iter()
+            .map(|o| {
+                let value = o.payoff(price(o.spec.underlying));
+                if o.written { -value } else { value }
+            })
+            .sum();
+        i64::from(state.cash) + shares + holdings + index + options - i64::from(state.financing.debt)
+    }
+
+    fn interest_model(&self) -> Result<InterestModel, GameError> {
+        self.config.interest.ok_or(GameError::BorrowingDisabled)
+    }
+
+    fn financing_event(
+        player_id: PlayerId,
+        cash_delta: i32,
+        debt: i32,
+    ) -> GameEvent {
+        GameEvent::FinancingUpdated {
+            player_id,
+            cash_delta,
+            debt,
+        }
+    }
+
+    pub(super) fn handle_borrow(
+        &mut self,
+        player_id: PlayerId,
+        amount: i32,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        self.require_trading("Borrow")?;
+        let model = self.interest_model()?;
+        if amount <= 0 {
+            return Err(GameError::NonPositiveAmount { amount });
+        }
+
+        let state = self.players.get(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
+        let equity = self.equity(player_id, state).max(0);
+        // Debt is held in i32, so a limit beyond that is no limit at all
+        let max_debt =
+            i32::try_from(equity * i64::from(model.max_leverage_pct.saturating_sub(100)) / 100).unwrap_or(i32::MAX);
+        let state = self.players.get_mut(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
+        if state.financing.debt.checked_add(amount).is_none_or(|debt| debt > max_debt) {
+            return Err(GameError::LeverageLimit {
+                debt: state.financing.debt,
+                requested: amount,
+                max_debt,
+            });
+        }
+
+        state.cash = state.cash.checked_add(amount).ok_or(GameError::AmountOutOfRange {
+            amount: i64::from(amount),
+        })?;
+        state.financing.debt += amount;
+        let event = Self::financing_event(player_id, amount, state.financing.debt);
+        Ok(self.broadcast(vec![event]))
+    }
+
+    /// Pays back up to `amount`, never more than is owed.
+    pub(super) fn handle_repay(
+        &mut self,
+        player_id: PlayerId,
+        amount: i32,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        self.require_trading("Repay")?;
+        self.interest_model()?;
+        if amount <= 0 {
+            return Err(GameError::NonPositiveAmount { amount });
+        }
+
+        let state = self.players.get_mut(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
+        let repaid = amount.min(state.financing.debt);
+        if i64::from(repaid) > state.available_cash() {
+            return Err(GameError::InsufficientFunds {
+                available: state.available_cash(),
+                required: i64::from(repaid),
+            });
+        }
+
+        state.cash -= repaid;
+        state.financing.debt -= repaid;
+        let event = Self::financing_event(player_id, -repaid, state.financing.debt);
+        Ok(self.broadcast(vec![event]))
+    }
+
+    /// Credits interest on idle cash and charges it on debt. Borrowed cash is
+    /// not idle, so it earns nothing. Only players whose booked balances
+    /// actually moved are reported.
+    pub(super) fn accrue_interest(&mut self) -> Vec<GameEvent> {
+        let Some(model) = self.config.interest else {
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        for (&player_id, state) in &mut self.players {
+            let idle = (state.available_cash() - i64::from(state.financing.debt)).max(0) as f64;
+            let financing = &mut state.financing;
+            financing.cash_carry += idle * f64::from(model.cash_rate_bps) / 10_000.0;
+            financing.debt_carry += f64::from(financing.debt) * f64::from(model.borrow_rate_bps) / 10_000.0;
+
+            let credited = financing.cash_carry.trunc();
+            let charged = financing.debt_carry.trunc();
+            financing.cash_carry -= credited;
+            financing.debt_carry -= charged;
+
+            if credited > 0.0 || charged > 0.0 {
+                state.cash = state.cash.saturating_add(credited as i32);
+                financing.debt = financing.debt.saturating_add(charged as i32);
+                events.push(Self::financing_event(player_id, credited as i32, financing.debt));
+            }
+        }
+        events
+    }
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+use crate::PlayerId;
+
+use super::order::{Fill, OrderKind, notional};
+use super::state::GameState;
+use super::ticker::Ticker;
+use super::{GameEvent, Side};
+
+/// A house liquidity provider quoting both sides of every player ticker.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MarketMakerConfig {
+    /// Distance of each quote from the ticker's price.
+    pub half_spread: i32,
+    /// Shares offered on each side per tick.
+    pub quote_size: u32,
+    /// Largest long or short inventory. A side that would breach it is pulled.
+    pub max_inventory: u32,
+    /// Ambient pressure per share of inventory, in units of base volatility.
+    /// A long market maker leans on the price, a short one props it up.
+    pub pressure_per_share: f32,
+}
+
+impl Default for MarketMakerConfig {
+    fn default() -> Self {
+        Self {
+            half_spread: 2,
+            quote_size: 3,
+            max_inventory: 15,
+            pressure_per_share: 0.02,
+        }
+    }
+}
+
+/// The market maker's current prices on one ticker; `None` when a side is pulled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quote {
+    pub bid: Option<i32>,
+    pub ask: Option<i32>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub(super) struct MarketMaker {
+    /// Shares bought from players less shares sold to them.
+    pub(super) inventory: i32,
+    pub(super) quote: Quote,
+    bid_left: u32,
+    ask_left: u32,
+}
+
+impl GameState {
+    /// Ambient pressure the market maker's inventory puts on `ticker`.
+    pub(super) fn inventory_pressure(
+        &self,
+        ticker: PlayerId,
+    ) -> f32 {
+        match (self.config.market_maker, self.market_makers.get(&ticker)) {
+            (Some(config), Some(mm)) => -(mm.inventory as f32) * config.pressure_per_share,
+            _ => 0.0,
+        }
+    }
+
+    /// Re-centers every quote on the ticker's price and restores its size.
+    /// Returns an event for each quote that changed.
+    pub(super) fn refresh_quotes(&mut self) -> Vec<GameEvent> {
+        let Some(config) = self.config.market_maker else {
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        for (&player_id, mm) in &mut self.market_makers {
+            let Some(price) = self.player_tickers.get(&player_id).map(|pt| pt.current_price) else {
+                continue;
+            };
+            let limit = config.max_inventory as i32;
+            mm.bid_left = config.quote_size.min((limit - mm.inventory).max(0) as u32);
+            mm.ask_left = config.quote_size.min((limit + mm.inventory).max(0) as u32);
+
+            let bid = price - config.half_spread;
+            let quote = Quote {
+                bid: (mm.bid_left > 0 && bid > 0).then_some(bid),
+                ask: (mm.ask_left > 0).then_some(price + config.half_spread),
+            };
+            if quote != mm.quote {
+                mm.quote = quote;
+                events.push(GameEvent::QuoteUpdated {
+                    player_id,
+                    quote,
+                    inventory: mm.inventory,
+                });
+            }
+        }
+        events
+    }
+
+    /// Fills resting limit orders that cross the market maker's quotes, at
+    /// the quote price, until the quote's size for this tick is used up. Runs
+    /// after the house, so it only takes what `liquidity_per_tick` left over.
+    pub(super) fn trade_with_market_makers(&mut self) -> Vec<GameEvent> {
+        let mut resolved = Vec::new();
+        for (player_id, mm) in &mut self.market_makers {
+            let Some(state) = self.players.get_mut(player_id) else {
+                continue;
+            };
+            let mut triggered = Vec::new();
+
+            if let Some(ask) = mm.quote.ask {
+                for i in 0..state.open_bids.len() {
+                    if mm.ask_left == 0 {
+                        break;
+                    }
+                    let order = state.open_bids[i];
+                    if order.kind != OrderKind::Limit
+                        || order.price < ask
+                        || order.group.is_some_and(|g| triggered.contains(&g))
+                    {
+                        continue;
+                    }
+                    let filled = order.quantity.min(mm.ask_left).min(state.affordable(&order, ask));
+                    let bid = &mut state.open_bids[i];
+                    if filled == 0 {
+                        continue;
+                    }
+                    mm.ask_left -= filled;
+                    mm.inventory -= filled as i32;
+                    bid.quantity -= filled;
+                    state.shares.extend(std::iter::repeat_n(ask, filled as usize));
+                    state.cash -= ask * filled as i32;
+                    let group = bid.group;
+                    if let Some(g) = group
+                        && state.groups.get(&g).is_some_and(|grp| grp.pending_exits.is_none())
+                    {
+                        bid.group = None;
+                        triggered.push(g);
+                    }
+                    state.trades += 1;
+                    resolved.push((
+                        Side::Bid,
+                        Fill {
+                            player_id: *player_id,
+                            order_price: bid.price,
+                            fill_price: ask,
+                            quantity: filled,
+                            remaining: bid.quantity,
+                            group,
+                        },
+                    ));
+                }
+                state.open_bids.retain(|b| b.quantity > 0);
+            }
+
+            if let Some(bid) = mm.quote.bid {
+                for ask in state.open_asks.iter_mut() {
+                    if mm.bid_left == 0 {
+                        break;
+                    }
+                    if ask.kind != OrderKind::Limit || ask.price > bid || ask.group.is_some_and(|g| triggered.contains(&g)) {
+                        continue;
+                    }
+                    let filled = ask.quantity.min(mm.bid_left).min(state.shares.len() as u32);
+                    if filled == 0 {
+                        continue;
+                    }
+                    mm.bid_left -= filled;
+                    mm.inventory += filled as i32;
+                    ask.quantity -= filled;
+                    state.shares.truncate(state.shares.len() - filled as usize);
+                    state.cash += bid * filled as i32;
+                    let group = ask.group;
+                    if let Some(g) = group
+                        && state.groups.get(&g).is_some_and(|grp| grp.pending_exits.is_none())
+                    {
+                        ask.group = None;
+                        triggered.push(g);
+                    }
+                    state.trades += 1;
+                    resolved.push((
+                        Side::Ask,
+                        Fill {
+                            player_id: *player_id,
+                            order_price: ask.price,
+                            fill_price: bid,
+                            quantity: filled,
+                            remaining: ask.quantity,
+                            group,
+                        },
+                    ));
+                }
+                state.open_asks.retain(|a| a.quantity > 0);
+            }
+        }
+
+        for (side, fill) in &resolved {
+            let impact = match side {
+                Side::Bid => Ticker::on_bid_filled,
+                Side::Ask => Ticker::on_ask_filled,
+            };
+            self.apply_impact(fill.player_id, notional(fill.fill_price, fill.quantity) as f32, impact);
+        }
+
+        let mut events = Vec::new();
+        for (side, fill) in resolved {
+            events.push(match side {
+                Side::Bid => GameEvent::BidFilled {
+                    player_id: fill.player_id,
+                    bid_value: fill.order_price,
+                    quantity: fill.quantity,
+                    remaining: fill.remaining,
+                },
+                Side::Ask => GameEvent::AskFilled {
+                    player_id: fill.player_id,
+                    ask_value: fill.order_price,
+                    quantity: fill.quantity,
+                    remaining: fill.remaining,
+                },
+            });
+            events.extend(self.cascade_fill(&fill));
+        }
+        events
+    }
+}
@@ -1,55 +1,265 @@
+mod auction;
+mod cadence;
+mod correlation;
+mod groups;
+mod index;
+mod insider;
+mod interest;
+mod market_maker;
+mod offers;
+mod options;
+mod order;
+mod rules;
+mod settlement;
 mod state;
 mod ticker;
+mod view;
+mod volatility;
 
 #[cfg(test)]
 mod tests;
 
+pub use cadence::{TickCurve, TickStep};
+pub use correlation::Correlation;
+pub use index::IndexConfig;
+pub use insider::{Direction, InsiderConfig, InsiderTip};
+pub use interest::InterestModel;
+pub use market_maker::{MarketMakerConfig, Quote};
+pub use offers::{OfferId, OfferStatus, TradeOffer};
+pub use options::{ContractId, OptionKind, OptionSpec, OptionsConfig};
+pub use order::{GroupId, GroupStatus, OrderKind, OrderSpec};
+pub use rules::OrderRules;
+pub use settlement::{PlayerSettlement, Settlement};
 pub use state::GameState;
 pub use ticker::{Decay, MarketConditions, MarketForce, PlayerTicker, Ticker};
+pub use view::{OpenOrder, OptionHolding, PendingBracket, PlayerView};
+pub use volatility::{Regime, RegimeSwitching, VolatilitySchedule, VolatilityWindow};
 
 use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
+
 use crate::PlayerId;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum GameAction {
     Countdown(u32),
     Start,
     Tick,
-    Bid { player_id: PlayerId, bid_value: i32 },
-    Ask { player_id: PlayerId, ask_value: i32 },
-    CancelBid { player_id: PlayerId, price: i32 },
-    CancelAsk { player_id: PlayerId, price: i32 },
+    Bid {
+        player_id: PlayerId,
+        bid_value: i32,
+        quantity: u32,
+    },
+    Ask {
+        player_id: PlayerId,
+        ask_value: i32,
+        quantity: u32,
+    },
+    CancelBid {
+        player_id: PlayerId,
+        price: i32,
+    },
+    CancelAsk {
+        player_id: PlayerId,
+        price: i32,
+    },
+    AmendOrder {
+        player_id: PlayerId,
+        side: Side,
+        price: i32,
+        new_price: i32,
+        new_quantity: u32,
+    },
+    CancelAll {
+        player_id: PlayerId,
+        side: Option<Side>,
+    },
+    /// Two linked orders where the first fill on either cancels the other.
+    PlaceOco {
+        player_id: PlayerId,
+        first: OrderSpec,
+        second: OrderSpec,
+    },
+    /// An entry bid that, once completely filled, arms a take-profit ask and a
+    /// stop-loss ask linked as an OCO pair.
+    PlaceBracket {
+        player_id: PlayerId,
+        entry_price: i32,
+        quantity: u32,
+        take_profit: i32,
+        stop_loss: i32,
+    },
+    /// Takes on margin debt, paid out as cash.
+    Borrow {
+        player_id: PlayerId,
+        amount: i32,
+    },
+    Repay {
+        player_id: PlayerId,
+        amount: i32,
+    },
+    /// Buys (`Side::Bid`) or sells (`Side::Ask`) the composite index at market.
+    TradeIndex {
+        player_id: PlayerId,
+        side: Side,
+        quantity: u32,
+    },
+    BuyOption {
+        player_id: PlayerId,
+        spec: OptionSpec,
+    },
+    /// Sells an option to the house, locking margin until it settles.
+    WriteOption {
+        player_id: PlayerId,
+        spec: OptionSpec,
+    },
+    /// Privately offers `quantity` shares of `ticker` to `buyer` at `price` each.
+    ProposeOffer {
+        seller: PlayerId,
+        buyer: PlayerId,
+        ticker: PlayerId,
+        quantity: u32,
+        price: i32,
+    },
+    RespondOffer {
+        player_id: PlayerId,
+        offer_id: OfferId,
+        accept: bool,
+    },
+    ExpireOffer {
+        offer_id: OfferId,
+    },
     End,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum GamePhase {
     Pending,
+    /// Optional opening call auction: orders are collected during the
+    /// countdown and executed at a single clearing price on `Start`.
+    Auction,
     Running,
     Ended,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GameConfig {
     pub tick_interval: Duration,
+    /// Optional changes to `tick_interval` as the game progresses.
+    pub tick_curve: TickCurve,
     pub game_duration: Duration,
     pub max_price_delta: i32,
     pub starting_price: i32,
     pub countdown_duration: Duration,
     pub starting_balance: i32,
+    /// Maximum shares filled per ticker and side on a single tick.
+    pub liquidity_per_tick: u32,
+    pub order_rules: OrderRules,
+    /// Run an opening call auction during the countdown instead of opening
+    /// every ticker at `starting_price`.
+    pub opening_auction: bool,
+    pub settlement: Settlement,
+    /// Spillover of order activity from each ticker onto the others.
+    pub correlation: Correlation,
+    pub volatility_schedule: VolatilitySchedule,
+    /// Optional market-wide switching between calm and turbulent regimes.
+    pub regimes: Option<RegimeSwitching>,
+    /// Interest on cash and margin borrowing. `None` disables both.
+    pub interest: Option<InterestModel>,
+    pub index: IndexConfig,
+    /// Options trading; `None` leaves it disabled.
+    pub options: Option<OptionsConfig>,
+    /// How long a player has to answer a trade offer.
+    pub offer_timeout: Duration,
+    /// Most unanswered offers a player may have out at once.
+    #[serde(default = "default_max_open_offers")]
+    pub max_open_offers: usize,
+    /// Built-in liquidity provider on every ticker; `None` leaves only the house.
+    pub market_maker: Option<MarketMakerConfig>,
+    /// Private tips about upcoming forces; `None` disables them.
+    pub insider: Option<InsiderConfig>,
 }
 
 impl Default for GameConfig {
     fn default() -> Self {
         Self {
             tick_interval: Duration::from_millis(250),
+            tick_curve: TickCurve::default(),
             game_duration: Duration::from_secs(180),
             max_price_delta: 25,
             starting_price: 100,
             countdown_duration: Duration::from_secs(3),
             starting_balance: 1000,
+            liquidity_per_tick: 10,
+            order_rules: OrderRules::default(),
+            opening_auction: false,
+            settlement: Settlement::default(),
+            correlation: Correlation::default(),
+            volatility_schedule: VolatilitySchedule::default(),
+            regimes: None,
+            interest: None,
+            index: IndexConfig::default(),
+            options: None,
+            offer_timeout: Duration::from_secs(15),
+            max_open_offers: default_max_open_offers(),
+            market_maker: None,
+            insider: None,
+        }
+    }
+}
+
+fn default_max_open_offers() -> usize {
+    5
+}
+
+impl GameConfig {
+    /// Rejects settings the game cannot run with. Checked before launch, since
+    /// a bad value would otherwise only surface mid-game.
+    pub fn validate(&self) -> Result<(), GameError> {
+        // A zero interval would tick forever without the game ever advancing
+        let intervals = std::iter::once(self.tick_interval).chain(self.tick_curve.steps.iter().map(|s| s.interval));
+        for interval in intervals {
+            if interval.is_zero() {
+                return Err(GameError::InvalidConfig("tick intervals must be non-zero"));
+            }
+        }
+        // A zero duration would end the game on its first tick
+        if self.game_duration.is_zero() {
+            return Err(GameError::InvalidConfig("game duration must be non-zero"));
+        }
+        if self.tick_curve.steps.iter().any(|s| !s.from_progress.is_finite()) {
+            return Err(GameError::InvalidConfig("tick curve progress must be finite"));
+        }
+
+        let multipliers = self
+            .volatility_schedule
+            .windows
+            .iter()
+            .map(|w| w.multiplier)
+            .chain(self.regimes.iter().flat_map(|r| [r.calm_multiplier, r.turbulent_multiplier]));
+        for multiplier in multipliers {
+            if !multiplier.is_finite() || multiplier < 0.0 {
+                return Err(GameError::InvalidConfig(
+                    "volatility multipliers must be finite and non-negative",
+                ));
+            }
         }
+
+        // Otherwise borrowing to hold cash would pay for itself
+        if let Some(interest) = self.interest
+            && interest.cash_rate_bps >= interest.borrow_rate_bps
+        {
+            return Err(GameError::InvalidConfig("cash rate must be below the borrow rate"));
+        }
+        Ok(())
     }
 }
 
@@ -64,42 +274,146 @@ pub enum GameEffect {
 #[derive(Clone, Debug)]
 pub enum GameEvent {
     Countdown(u32),
+    AuctionOpened {
+        reference_price: i32,
+    },
+    /// The opening price a ticker's auction settled on.
+    AuctionCleared {
+        player_id: PlayerId,
+        price: i32,
+    },
     GameStarted {
         starting_price: i32,
         starting_balance: i32,
         players: Vec<PlayerId>,
         game_duration_secs: u64,
+        tick_interval_ms: u64,
     },
     PriceChanged {
         player_id: PlayerId,
         price: i32,
     },
+    RegimeChanged {
+        regime: Regime,
+    },
+    /// The market maker on `player_id`'s ticker moved its quotes.
+    QuoteUpdated {
+        player_id: PlayerId,
+        quote: Quote,
+        inventory: i32,
+    },
+    /// Prices will now update every `interval_ms` milliseconds.
+    TickIntervalChanged {
+        interval_ms: u64,
+    },
     BidPlaced {
         player_id: PlayerId,
         bid_value: i32,
+        quantity: u32,
     },
     AskPlaced {
         player_id: PlayerId,
         ask_value: i32,
+        quantity: u32,
     },
+    /// `quantity` shares were bought this tick; `remaining` are still resting.
     BidFilled {
         player_id: PlayerId,
         bid_value: i32,
+        quantity: u32,
+        remaining: u32,
     },
+    /// `quantity` shares were sold this tick; `remaining` are still resting.
     AskFilled {
         player_id: PlayerId,
         ask_value: i32,
+        quantity: u32,
+        remaining: u32,
     },
     BidCanceled {
         player_id: PlayerId,
         price: i32,
+        quantity: u32,
     },
     AskCanceled {
         player_id: PlayerId,
         price: i32,
+        quantity: u32,
+    },
+    OrderAmended {
+        player_id: PlayerId,
+        side: Side,
+        price: i32,
+        new_price: i32,
+        new_quantity: u32,
+    },
+    /// Every order removed by a single `GameAction::CancelAll`, reported as one
+    /// batch of `(price, quantity)` pairs.
+    OrdersCanceled {
+        player_id: PlayerId,
+        bids: Vec<(i32, u32)>,
+        asks: Vec<(i32, u32)>,
+    },
+    OrderGroupUpdated {
+        player_id: PlayerId,
+        group_id: GroupId,
+        status: GroupStatus,
+    },
+    /// `position` is the player's index holding after the trade; negative when short.
+    IndexTraded {
+        player_id: PlayerId,
+        side: Side,
+        quantity: u32,
+        price: i32,
+        position: i32,
+    },
+    /// `premium` is the total paid when buying, or received when writing.
+    OptionTraded {
+        player_id: PlayerId,
+        contract_id: ContractId,
+        spec: OptionSpec,
+        written: bool,
+        premium: i32,
+    },
+    /// An option expired and was cash settled at intrinsic value.
+    OptionSettled {
+        player_id: PlayerId,
+        contract_id: ContractId,
+        cash_delta: i32,
+    },
+    /// Sent to the seller and buyer only.
+    OfferProposed {
+        offer: TradeOffer,
+        expires_in_ms: u64,
+    },
+    OfferResolved {
+        offer_id: OfferId,
+        seller: PlayerId,
+        buyer: PlayerId,
+        status: OfferStatus,
+    },
+    /// Cash moved by borrowing, repaying or interest rather than trading.
+    /// `debt` is the player's outstanding margin debt afterwards.
+    FinancingUpdated {
+        player_id: PlayerId,
+        cash_delta: i32,
+        debt: i32,
+    },
+    /// Sent to a single player: a force is expected on `ticker` in about
+    /// `in_ticks` ticks. It may never come.
+    InsiderTip {
+        ticker: PlayerId,
+        direction: Direction,
+        in_ticks: u32,
+    },
+    /// Every tip handed out during the game, shown to all once it ends.
+    InsiderTipsRevealed {
+        tips: Vec<InsiderTip>,
     },
     GameEnded {
         final_balances: Vec<(PlayerId, i32)>,
+        /// How each player's final balance was arrived at under `GameConfig::settlement`.
+        settlements: Vec<PlayerSettlement>,
     },
 }
 
@@ -111,7 +425,7 @@ pub enum GameError {
     InvalidPhase { action: &'static str, phase: GamePhase },
 
     #[error("insufficient funds: have {available}, need {required}")]
-    InsufficientFunds { available: i32, required: i32 },
+    InsufficientFunds { available: i64, required: i64 },
 
     #[error("insufficient shares: have {available}, need {required}")]
     InsufficientShares { available: usize, required: usize },
@@ -121,4 +435,61 @@ pub enum GameError {
 
     #[error("{order_type} order at price {price} not found")]
     OrderNotFound { order_type: String, price: i32 },
+
+    #[error("price must be positive, got {price}")]
+    NonPositivePrice { price: i32 },
+
+    #[error("price {price} is not a multiple of tick size {tick_size}")]
+    OffTick { price: i32, tick_size: i32 },
+
+    #[error("price {price} outside allowed band [{min}, {max}]")]
+    OutsidePriceBand { price: i32, min: i32, max: i32 },
+
+    #[error("order quantity must be at least 1")]
+    ZeroQuantity,
+
+    #[error("quantity {quantity} exceeds the limit of {max}")]
+    QuantityTooLarge { quantity: u32, max: u32 },
+
+    #[error("amount {amount} is out of range")]
+    AmountOutOfRange { amount: i64 },
+
+    #[error("bracket stop-loss {stop_loss} must be below take-profit {take_profit}")]
+    InvalidBracket { take_profit: i32, stop_loss: i32 },
+
+    #[error("too many open orders: {open} open, limit is {limit}")]
+    TooManyOpenOrders { open: usize, limit: usize },
+
+    #[error("borrowing is not enabled in this game")]
+    BorrowingDisabled,
+
+    #[error("amount must be positive, got {amount}")]
+    NonPositiveAmount { amount: i32 },
+
+    #[error("borrowing {requested} on top of {debt} exceeds the limit of {max_debt}")]
+    LeverageLimit { debt: i32, requested: i32, max_debt: i32 },
+
+    #[error("index position {position} would exceed the limit of {limit}")]
+    IndexPositionLimit { position: i64, limit: i64 },
+
+    #[error("options are not enabled in this game")]
+    OptionsDisabled,
+
+    #[error("strike {strike} outside allowed range [{min}, {max}]")]
+    StrikeOutOfRange { strike: i32, min: i32, max: i32 },
+
+    #[error("option must expire at least one tick from now")]
+    InvalidExpiry,
+
+    #[error("cannot trade with player {0:?}")]
+    InvalidCounterparty(PlayerId),
+
+    #[error("offer {0:?} not found")]
+    OfferNotFound(OfferId),
+
+    #[error("too many open offers: {open} open, limit is {limit}")]
+    TooManyOpenOffers { open: usize, limit: usize },
+
+    #[error("invalid game config: {0}")]
+    InvalidConfig(&'static str),
 }
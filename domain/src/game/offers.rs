@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+
+use crate::PlayerId;
+
+use super::order::{notional, to_cash};
+use super::state::{GameState, PlayerState};
+use super::{GameAction, GameEffect, GameError, GameEvent, GamePhase};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OfferId(pub u32);
+
+/// A private proposal from `seller` to sell `quantity` shares of `ticker` to
+/// `buyer` at `price` each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeOffer {
+    pub offer_id: OfferId,
+    pub seller: PlayerId,
+    pub buyer: PlayerId,
+    pub ticker: PlayerId,
+    pub quantity: u32,
+    pub price: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OfferStatus {
+    Accepted,
+    Rejected,
+    Expired,
+}
+
+impl PlayerState {
+    /// Shares of `ticker` this player could hand over right now. Own-ticker
+    /// shares already promised to resting asks don't count.
+    fn transferable(
+        &self,
+        owner: PlayerId,
+        ticker: PlayerId,
+    ) -> usize {
+        if ticker == owner {
+            self.available_shares()
+        } else {
+            self.holdings.get(&ticker).copied().unwrap_or(0) as usize
+        }
+    }
+
+    fn remove_shares(
+        &mut self,
+        owner: PlayerId,
+        ticker: PlayerId,
+        quantity: u32,
+    ) {
+        if ticker == owner {
+            let kept = self.shares.len() - quantity as usize;
+            self.shares.truncate(kept);
+        } else if let Some(held) = self.holdings.get_mut(&ticker) {
+            *held -= quantity;
+            if *held == 0 {
+                self.holdings.remove(&ticker);
+            }
+        }
+    }
+
+    fn add_shares(
+        &mut self,
+        owner: PlayerId,
+        ticker: PlayerId,
+        quantity: u32,
+        price: i32,
+    ) {
+        if ticker == owner {
+            self.shares.extend(std::iter::repeat_n(price, quantity as usize));
+        } else {
+            *self.holdings.entry(ticker).or_default() += quantity;
+        }
+    }
+}
+
+impl GameState {
+    /// Sends `event` to the two parties of an offer only.
+    fn notify_parties(
+        offer: &TradeOffer,
+        event: GameEvent,
+    ) -> Vec<GameEffect> {
+        [offer.seller, offer.buyer]
+            .into_iter()
+            .map(|player_id| GameEffect::Notification {
+                player_id,
+                event: event.clone(),
+            })
+            .collect()
+    }
+
+    fn resolve_offer(
+        offer: &TradeOffer,
+        status: OfferStatus,
+    ) -> Vec<GameEffect> {
+        Self::notify_parties(
+            offer,
+            GameEvent::OfferResolved {
+                offer_id: offer.offer_id,
+                seller: offer.seller,
+                buyer: offer.buyer,
+                status,
+            },
+        )
+    }
+
+    pub(super) fn handle_propose_offer(
+        &mut self,
+        seller: PlayerId,
+        buyer: PlayerId,
+        ticker: PlayerId,
+        quantity: u32,
+        price: i32,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        self.require_phase(GamePhase::Running, "ProposeOffer")?;
+        if buyer == seller || !self.players.contains_key(&buyer) {
+            return Err(GameError::InvalidCounterparty(buyer));
+        }
+        if !self.player_tickers.contains_key(&ticker) {
+            return Err(GameError::PlayerNotFound(ticker));
+        }
+        self.config.order_rules.check_quantity(quantity)?;
+        if price <= 0 {
+            return Err(GameError::NonPositivePrice { price });
+        }
+
+        // Every offer holds an expiry timer until it is answered
+        let open = self.offers.values().filter(|o| o.seller == seller).count();
+        if open >= self.config.max_open_offers {
+            return Err(GameError::TooManyOpenOffers {
+                open,
+                limit: self.config.max_open_offers,
+            });
+        }
+
+        // Checked again on acceptance; this just rejects offers that could never settle
+        let state = self.players.get(&seller).ok_or(GameError::PlayerNotFound(seller))?;
+        let available = state.transferable(seller, ticker);
+        if available < quantity as usize {
+            return Err(GameError::InsufficientShares {
+                available,
+                required: quantity as usize,
+            });
+        }
+
+        self.next_offer_id += 1;
+        let offer = TradeOffer {
+            offer_id: OfferId(self.next_offer_id),
+            seller,
+            buyer,
+            ticker,
+            quantity,
+            price,
+        };
+        self.offers.insert(offer.offer_id, offer);
+
+        let timeout = self.config.offer_timeout;
+        let mut effects = Self::notify_parties(
+            &offer,
+            GameEvent::OfferProposed {
+                offer,
+                expires_in_ms: timeout.as_millis() as u64,
+            },
+        );
+        effects.push(GameEffect::DelayedAction {
+            delay: timeout,
+            action: GameAction::ExpireOffer {
+                offer_id: offer.offer_id,
+            },
+        });
+        Ok(effects)
+    }
+
+    /// Only the buyer may respond. Acceptance settles the trade immediately,
+    /// provided both sides can still honor it; otherwise the offer stays open.
+    pub(super) fn handle_respond_offer(
+        &mut self,
+        player_id: PlayerId,
+        offer_id: OfferId,
+        accept: bool,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        self.require_phase(GamePhase::Running, "RespondOffer")?;
+        let offer = self
+            .offers
+            .get(&offer_id)
+            .copied()
+            .filter(|o| o.buyer == player_id)
+            .ok_or(GameError::OfferNotFound(offer_id))?;
+
+        if !accept {
+            self.offers.remove(&offer_id);
+            return Ok(Self::resolve_offer(&offer, OfferStatus::Rejected));
+        }
+
+        let cost = notional(offer.price, offer.quantity);
+        let buyer = self.players.get(&offer.buyer).ok_or(GameError::PlayerNotFound(offer.buyer))?;
+        if cost > buyer.available_cash() {
+            return Err(GameError::InsufficientFunds {
+                available: buyer.available_cash(),
+                required: cost,
+            });
+        }
+        let cost = to_cash(cost)?;
+        let seller = self
+            .players
+            .get(&offer.seller)
+            .ok_or(GameError::PlayerNotFound(offer.seller))?;
+        let available = seller.transferable(offer.seller, offer.ticker);
+        if available < offer.quantity as usize {
+            return Err(GameError::InsufficientShares {
+                available,
+                required: offer.quantity as usize,
+            });
+        }
+
+        if let Some(seller) = self.players.get_mut(&offer.seller) {
+            seller.remove_shares(offer.seller, offer.ticker, offer.quantity);
+            seller.cash += cost;
+            seller.trades += 1;
+        }
+        if let Some(buyer) = self.players.get_mut(&offer.buyer) {
+            buyer.add_shares(offer.buyer, offer.ticker, offer.quantity, offer.price);
+            buyer.cash -= cost;
+            buyer.trades += 1;
+        }
+        self.offers.remove(&offer_id);
+        Ok(Self::resolve_offer(&offer, OfferStatus::Accepted))
+    }
+
+    /// Fired by the offer's timer; a no-op if it was already answered.
+    pub(super) fn handle_expire_offer(
+        &mut self,
+        offer_id: OfferId,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        Ok(self
+            .offers
+            .remove(&offer_id)
+            .map(|offer| Self::resolve_offer(&offer, OfferStatus::Expired))
+            .unwrap_or_default())
+    }
+}
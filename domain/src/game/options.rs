@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+
+use crate::PlayerId;
+
+use super::order::{clamp_cash, notional, to_cash};
+use super::state::GameState;
+use super::{GameEffect, GameError, GameEvent, GamePhase};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionKind {
+    Call,
+    Put,
+}
+
+/// European options on player tickers, bought from and written to the house.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OptionsConfig {
+    /// Strikes must lie within this percentage of the underlying's price.
+    pub max_strike_distance_pct: u32,
+    /// House spread around the model price, in basis points each way.
+    pub spread_bps: u32,
+}
+
+impl Default for OptionsConfig {
+    fn default() -> Self {
+        Self {
+            max_strike_distance_pct: 20,
+            spread_bps: 500,
+        }
+    }
+}
+
+/// Terms of an option trade. `expiry_ticks` of `None` expires at game end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OptionSpec {
+    pub underlying: PlayerId,
+    pub kind: OptionKind,
+    pub strike: i32,
+    #[serde(default)]
+    pub expiry_ticks: Option<u32>,
+    pub quantity: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ContractId(pub u32);
+
+/// An option held (or written, when `written`) by a player.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(super) struct OptionPosition {
+    pub(super) id: ContractId,
+    pub(super) spec: OptionSpec,
+    pub(super) written: bool,
+    /// Ticks left until expiry, or `None` for game end.
+    pub(super) ticks_left: Option<u32>,
+    /// Cash locked against a written option until it settles.
+    pub(super) margin: i32,
+}
+
+impl OptionPosition {
+    /// What the position pays out at `underlying_price`. A writer never owes
+    /// more than the margin locked against the option; the house covers the rest.
+    pub(super) fn payoff(
+        &self,
+        underlying_price: i32,
+    ) -> i64 {
+        let (price, strike) = (i64::from(underlying_price), i64::from(self.spec.strike));
+        let intrinsic = match self.spec.kind {
+            OptionKind::Call => price - strike,
+            OptionKind::Put => strike - price,
+        };
+        let payoff = intrinsic.max(0) * i64::from(self.spec.quantity);
+        if self.written {
+            payoff.min(i64::from(self.margin))
+        } else {
+            payoff
+        }
+    }
+}
+
+/// Bachelier (normal model) value of one option. `sigma` is the standard
+/// deviation of the underlying's price over the option's remaining life.
+fn bachelier(
+    kind: OptionKind,
+    spot: f64,
+    strike: f64,
+    sigma: f64,
+) -> f64 {
+    let moneyness = match kind {
+        OptionKind::Call => spot - strike,
+        OptionKind::Put => strike - spot,
+    };
+    if sigma <= 0.0 {
+        return moneyness.max(0.0);
+    }
+    let d = moneyness / sigma;
+    moneyness * normal_cdf(d) + sigma * normal_pdf(d)
+}
+
+fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Abramowitz & Stegun 7.1.26; accurate to about 1e-7, plenty for game prices.
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * z);
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
+
+impl GameState {
+    fn options_config(&self) -> Result<OptionsConfig, GameError> {
+        self.config.options.ok_or(GameError::OptionsDisabled)
+    }
+
+    fn ticks_until_end(&self) -> u32 {
+        let left = self.config.game_duration.saturating_sub(self.elapsed);
+        (left.as_millis() / self.tick_interval.as_millis().max(1)).max(1) as u32
+    }
+
+    /// Model price of one contract, before the house spread.
+    pub(super) fn option_fair_value(
+        &self,
+        spec: &OptionSpec,
+    ) -> Result<f64, GameError> {
+        let player_ticker = self
+            .player_tickers
+            .get(&spec.underlying)
+            .ok_or(GameError::PlayerNotFound(spec.underlying))?;
+        let ticks = spec.expiry_ticks.unwrap_or_else(|| self.ticks_until_end());
+        // Each tick moves the price uniformly within +/- the effective volatility
+        let per_tick = f64::from(player_ticker.ticker.effective_volatility().max(0)) / 3f64.sqrt();
+        let sigma = per_tick * f64::from(ticks).sqrt();
+        Ok(bachelier(
+            spec.kind,
+            f64::from(player_ticker.current_price),
+            f64::from(spec.strike),
+            sigma,
+        ))
+    }
+
+    fn validate_option(
+        &self,
+        spec: &OptionSpec,
+        config: OptionsConfig,
+    ) -> Result<(), GameError> {
+        self.config.order_rules.check_quantity(spec.quantity)?;
+        if spec.expiry_ticks == Some(0) {
+            return Err(GameError::InvalidExpiry);
+        }
+
+        let spot = self
+            .player_tickers
+            .get(&spec.underlying)
+            .map(|pt| pt.current_price)
+            .ok_or(GameError::PlayerNotFound(spec.underlying))?;
+        let width = (i64::from(spot) * i64::from(config.max_strike_distance_pct) / 100) as i32;
+        let (min, max) = (spot - width, spot + width);
+        if spec.strike <= 0 || !(min..=max).contains(&spec.strike) {
+            return Err(GameError::StrikeOutOfRange {
+                strike: spec.strike,
+                min,
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    fn allocate_contract_id(&mut self) -> ContractId {
+        self.next_contract_id += 1;
+        ContractId(self.next_contract_id)
+    }
+
+    /// Buys (`written == false`) or writes an option against the house at the
+    /// model price, less or plus the spread. Writers lock margin: the strike
+    /// for puts, twice the spot for calls.
+    pub(super) fn handle_trade_option(
+        &mut self,
+        player_id: PlayerId,
+        spec: OptionSpec,
+        written: bool,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        self.require_phase(GamePhase::Running, if written { "WriteOption" } else { "BuyOption" })?;
+        let config = self.options_config()?;
+        self.validate_option(&spec, config)?;
+
+        let fair = self.option_fair_value(&spec)?;
+        let spread = f64::from(config.spread_bps) / 10_000.0;
+        let unit_premium = if written {
+            fair * (1.0 - spread)
+        } else {
+            fair * (1.0 + spread)
+        };
+        let premium = notional((unit_premium.round() as i32).max(1), spec.quantity);
+
+        let spot = self
+            .player_tickers
+            .get(&spec.underlying)
+            .map(|pt| pt.current_price)
+            .unwrap_or(0);
+        let margin = if written {
+            match spec.kind {
+                OptionKind::Call => 2 * notional(spot, spec.quantity),
+                OptionKind::Put => notional(spec.strike, spec.quantity),
+            }
+        } else {
+            0
+        };
+
+        let state = self.players.get(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
+        // Writers may put the premium they receive towards the margin
+        let required = if written { margin - premium } else { premium };
+        if required > state.available_cash() {
+            return Err(GameError::InsufficientFunds {
+                available: state.available_cash(),
+                required,
+            });
+        }
+        let (premium, margin) = (to_cash(premium)?, to_cash(margin)?);
+
+        let id = self.allocate_contract_id();
+        if let Some(state) = self.players.get_mut(&player_id) {
+            state.cash += if written { premium } else { -premium };
+            state.trades += 1;
+            state.options.push(OptionPosition {
+                id,
+                spec,
+                written,
+                ticks_left: spec.expiry_ticks,
+                margin,
+            });
+        }
+
+        Ok(self.broadcast(vec![GameEvent::OptionTraded {
+            player_id,
+            contract_id: id,
+            spec,
+            written,
+            premium,
+        }]))
+    }
+
+    /// Counts down tick-based expiries and cash settles whatever expired.
+    pub(super) fn expire_options(&mut self) -> Vec<GameEvent> {
+        for state in self.players.values_mut() {
+            for position in &mut state.options {
+                if let Some(ticks) = &mut position.ticks_left {
+                    *ticks = ticks.saturating_sub(1);
+                }
+            }
+        }
+        self.settle_options(|p| p.ticks_left == Some(0))
+    }
+
+    /// Cash settles every matching position against the house at intrinsic value.
+    pub(super) fn settle_options(
+        &mut self,
+        expired: impl Fn(&OptionPosition) -> bool,
+    ) -> Vec<GameEvent> {
+        let prices: Vec<(PlayerId, i32)> = self.player_tickers.iter().map(|(&pid, pt)| (pid, pt.current_price)).collect();
+        let price_of = |pid: PlayerId| prices.iter().find(|(p, _)| *p == pid).map_or(0, |&(_, price)| price);
+
+        let mut events = Vec::new();
+        for (&player_id, state) in &mut self.players {
+            for position in state.options.extract_if(.., |p| expired(p)) {
+                let payoff = position.payoff(price_of(position.spec.underlying));
+                let cash_delta = clamp_cash(if position.written { -payoff } else { payoff });
+                state.cash = clamp_cash(i64::from(state.cash) + i64::from(cash_delta));
+                events.push(GameEvent::OptionSettled {
+                    player_id,
+                    contract_id: position.id,
+                    cash_delta,
+                });
+            }
+        }
+        events
+    }
+}
@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+use crate::PlayerId;
+
+use super::{GameError, Side};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderKind {
+    /// Fills once the price is at or better than the order price.
+    #[default]
+    Limit,
+    /// Rests untriggered until the price crosses the order price (rising for
+    /// bids, falling for asks), then fills at whatever the market price is.
+    Stop,
+}
+
+/// Client-facing description of a single order, used for the legs of order groups.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderSpec {
+    pub side: Side,
+    #[serde(default)]
+    pub kind: OrderKind,
+    pub price: i32,
+    pub quantity: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GroupId(pub u32);
+
+/// Lifecycle of an order group as reported to clients.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum GroupStatus {
+    Placed {
+        legs: Vec<OrderSpec>,
+    },
+    /// A bracket's entry filled, completely or before the rest of it was
+    /// canceled, and its exit legs are now live for the shares it bought.
+    Armed,
+    /// A leg filled and every sibling was canceled.
+    Triggered,
+    /// A leg was canceled by the player, unlinking whatever was left.
+    Canceled,
+}
+
+/// A resting order. `quantity` is what is still unfilled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct Order {
+    pub(super) price: i32,
+    pub(super) quantity: u32,
+    pub(super) kind: OrderKind,
+    pub(super) group: Option<GroupId>,
+}
+
+impl Order {
+    pub(super) fn new(
+        price: i32,
+        quantity: u32,
+    ) -> Self {
+        Self {
+            price,
+            quantity,
+            kind: OrderKind::Limit,
+            group: None,
+        }
+    }
+
+    pub(super) fn from_spec(
+        spec: OrderSpec,
+        group: GroupId,
+    ) -> Self {
+        Self {
+            price: spec.price,
+            quantity: spec.quantity,
+            kind: spec.kind,
+            group: Some(group),
+        }
+    }
+
+    pub(super) fn notional(&self) -> i64 {
+        notional(self.price, self.quantity)
+    }
+
+    pub(super) fn bid_fillable(
+        &self,
+        current_price: i32,
+    ) -> bool {
+        match self.kind {
+            OrderKind::Limit => self.price >= current_price,
+            OrderKind::Stop => current_price >= self.price,
+        }
+    }
+
+    pub(super) fn ask_fillable(
+        &self,
+        current_price: i32,
+    ) -> bool {
+        match self.kind {
+            OrderKind::Limit => self.price <= current_price,
+            OrderKind::Stop => current_price <= self.price,
+        }
+    }
+}
+
+/// Exit legs of a bracket, held off the book until the entry has fully filled
+/// or the rest of it is canceled.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(super) struct BracketExits {
+    pub(super) take_profit: i32,
+    pub(super) stop_loss: i32,
+    pub(super) quantity: u32,
+    /// Shares the entry has bought so far, which is what the exits cover.
+    #[serde(default)]
+    pub(super) filled: u32,
+}
+
+/// Orders sharing a `GroupId` are one-cancels-other: the first fill on any of
+/// them cancels the rest. A bracket starts out as just its entry order plus
+/// `pending_exits`, and becomes a plain OCO pair once armed.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(super) struct OrderGroup {
+    pub(super) pending_exits: Option<BracketExits>,
+}
+
+/// Result of (partially) filling one order during a tick.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Fill {
+    pub(super) player_id: PlayerId,
+    pub(super) order_price: i32,
+    pub(super) fill_price: i32,
+    pub(super) quantity: u32,
+    pub(super) remaining: u32,
+    pub(super) group: Option<GroupId>,
+}
+
+/// Cost of `quantity` at `price`. Both come from clients, so the product is
+/// taken in `i64` where it cannot overflow.
+pub(super) fn notional(
+    price: i32,
+    quantity: u32,
+) -> i64 {
+    i64::from(price) * i64::from(quantity)
+}
+
+/// Narrows an amount computed by `notional` back to a cash value.
+pub(super) fn to_cash(amount: i64) -> Result<i32, GameError> {
+    i32::try_from(amount).map_err(|_| GameError::AmountOutOfRange { amount })
+}
+
+/// Narrows an amount to a cash value, clamping what does not fit. For totals
+/// that have to be reported whatever they come to, such as at settlement.
+pub(super) fn clamp_cash(amount: i64) -> i32 {
+    amount.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+}
+
+/// Sums a side's reservations, counting each group once at its largest leg
+/// since at most one leg of a group can ever fill.
+pub(super) fn reserved(
+    orders: &[Order],
+    weight: impl Fn(&Order) -> i64,
+) -> i64 {
+    let mut ungrouped = 0;
+    let mut grouped: Vec<(GroupId, i64)> = Vec::new();
+    for order in orders {
+        match order.group {
+            None => ungrouped += weight(order),
+            Some(group) => match grouped.iter_mut().find(|(g, _)| *g == group) {
+                Some((_, max)) => *max = (*max).max(weight(order)),
+                None => grouped.push((group, weight(order))),
+            },
+        }
+    }
+    ungrouped + grouped.iter().map(|(_, w)| w).sum::<i64>()
+}
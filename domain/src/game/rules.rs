@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use super::GameError;
+
+/// Limits every order must satisfy before it is accepted into the book.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderRules {
+    /// Prices must be a whole multiple of this value.
+    pub tick_size: i32,
+    /// Allowed distance from the current price, as a percentage of it.
+    /// `None` disables the band entirely.
+    pub price_band_pct: Option<u32>,
+    /// Maximum number of resting orders (bids and asks combined) per player.
+    pub max_open_orders: usize,
+    /// Largest quantity a single order, trade or offer may be for.
+    #[serde(default = "default_max_quantity")]
+    pub max_quantity: u32,
+}
+
+fn default_max_quantity() -> u32 {
+    1000
+}
+
+impl Default for OrderRules {
+    fn default() -> Self {
+        Self {
+            tick_size: 1,
+            price_band_pct: Some(50),
+            max_open_orders: 10,
+            max_quantity: default_max_quantity(),
+        }
+    }
+}
+
+impl OrderRules {
+    pub fn check_price(
+        &self,
+        price: i32,
+        current_price: i32,
+    ) -> Result<(), GameError> {
+        if price <= 0 {
+            return Err(GameError::NonPositivePrice { price });
+        }
+
+        if self.tick_size > 1 && price % self.tick_size != 0 {
+            return Err(GameError::OffTick {
+                price,
+                tick_size: self.tick_size,
+            });
+        }
+
+        if let Some((min, max)) = self.price_band(current_price)
+            && !(min..=max).contains(&price)
+        {
+            return Err(GameError::OutsidePriceBand { price, min, max });
+        }
+
+        Ok(())
+    }
+
+    pub fn check_quantity(
+        &self,
+        quantity: u32,
+    ) -> Result<(), GameError> {
+        if quantity == 0 {
+            return Err(GameError::ZeroQuantity);
+        }
+        if quantity > self.max_quantity {
+            return Err(GameError::QuantityTooLarge {
+                quantity,
+                max: self.max_quantity,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check_open_orders(
+        &self,
+        open: usize,
+    ) -> Result<(), GameError> {
+        if open >= self.max_open_orders {
+            return Err(GameError::TooManyOpenOrders {
+                open,
+                limit: self.max_open_orders,
+            });
+        }
+        Ok(())
+    }
+
+    /// Inclusive `(min, max)` range of acceptable prices around `current_price`.
+    pub fn price_band(
+        &self,
+        current_price: i32,
+    ) -> Option<(i32, i32)> {
+        self.price_band_pct.map(|pct| {
+            let width = (i64::from(current_price) * i64::from(pct) / 100) as i32;
+            (current_price - width, current_price + width)
+        })
+    }
+}
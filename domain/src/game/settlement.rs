@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::PlayerId;
+
+/// How shares still held when the game ends are turned into cash.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Settlement {
+    /// Every share is worth the final price, however many are held.
+    #[default]
+    MarkToMarket,
+    /// All shares are sold in a single closing auction. The house bids
+    /// `liquidity_per_tick` shares at every tick below the final price, and the
+    /// whole position clears at the lowest level it has to reach.
+    ClosingAuction,
+    /// Shares are sold one at a time, each one `bps_per_share` basis points of
+    /// the final price cheaper than the last.
+    PriceImpact { bps_per_share: u32 },
+}
+
+/// One player's line of the end-of-game settlement.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PlayerSettlement {
+    pub player_id: PlayerId,
+    pub cash: i32,
+    pub shares: u32,
+    pub final_price: i32,
+    /// Average price the shares were liquidated at.
+    pub settlement_price: i32,
+    pub proceeds: i32,
+    pub index_position: i32,
+    pub index_value: i32,
+    /// Shares of other players' tickers, at those tickers' final prices.
+    pub holdings_value: i32,
+    pub debt: i32,
+    pub trades: u32,
+    pub final_balance: i32,
+}
+
+impl Settlement {
+    /// Cash raised by liquidating `shares` on a ticker last traded at `final_price`.
+    /// Left in `i64` for the caller to narrow once it has totalled everything.
+    pub(super) fn proceeds(
+        self,
+        shares: u32,
+        final_price: i32,
+        tick_size: i32,
+        liquidity: u32,
+    ) -> i64 {
+        if shares == 0 {
+            return 0;
+        }
+        let final_price = i64::from(final_price.max(0));
+        let shares = i64::from(shares);
+
+        match self {
+            Settlement::MarkToMarket => shares * final_price,
+            Settlement::ClosingAuction => {
+                let levels = (shares - 1) / i64::from(liquidity.max(1));
+                let clearing = (final_price - levels * i64::from(tick_size.max(1))).max(0);
+                shares * clearing
+            }
+            Settlement::PriceImpact { bps_per_share } => (0..shares)
+                .map(|i| (final_price * (10_000 - i * i64::from(bps_per_share)) / 10_000).max(0))
+                .sum(),
+        }
+    }
+}
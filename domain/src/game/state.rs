@@ -1,17 +1,41 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
-use crate::PlayerId;
+use serde::{Deserialize, Serialize};
 
-use super::ticker::PlayerTicker;
-use super::{GameAction, GameConfig, GameEffect, GameError, GameEvent, GamePhase};
+use crate::PlayerId;
 
-#[derive(Clone, Debug)]
+use super::auction::clearing_price;
+use super::insider::{InsiderTip, ScheduledForce};
+use super::interest::Financing;
+use super::market_maker::MarketMaker;
+use super::offers::{OfferId, TradeOffer};
+use super::options::OptionPosition;
+use super::order::{BracketExits, Fill, GroupId, GroupStatus, Order, OrderGroup, OrderKind, clamp_cash, notional, reserved};
+use super::settlement::PlayerSettlement;
+use super::ticker::{MarketConditions, PlayerTicker, Ticker};
+use super::volatility::Regime;
+use super::{GameAction, GameConfig, GameEffect, GameError, GameEvent, GamePhase, Side};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub(super) struct PlayerState {
     pub(super) cash: i32,
     pub(super) shares: Vec<i32>,
-    pub(super) open_bids: Vec<i32>,
-    pub(super) open_asks: Vec<i32>,
+    pub(super) open_bids: Vec<Order>,
+    pub(super) open_asks: Vec<Order>,
+    pub(super) groups: HashMap<GroupId, OrderGroup>,
+    pub(super) financing: Financing,
+    /// Units of the composite index held; negative when short.
+    pub(super) index_position: i32,
+    /// Proceeds of the open short index position, held back until it is covered.
+    #[serde(default)]
+    pub(super) index_margin: i32,
+    pub(super) options: Vec<OptionPosition>,
+    /// Shares of other players' tickers, acquired through trade offers.
+    pub(super) holdings: HashMap<PlayerId, u32>,
+    /// Order fills, index and option trades, and accepted offers.
+    #[serde(default)]
+    pub(super) trades: u32,
 }
 
 impl PlayerState {
@@ -21,32 +45,120 @@ impl PlayerState {
             shares: Vec::new(),
             open_bids: Vec::new(),
             open_asks: Vec::new(),
+            groups: HashMap::new(),
+            financing: Financing::default(),
+            index_position: 0,
+            index_margin: 0,
+            options: Vec::new(),
+            holdings: HashMap::new(),
+            trades: 0,
+        }
+    }
+
+    pub(super) fn available_cash(&self) -> i64 {
+        let margin: i64 = self.options.iter().map(|o| i64::from(o.margin)).sum::<i64>() + i64::from(self.index_margin);
+        i64::from(self.cash) - reserved(&self.open_bids, Order::notional) - margin
+    }
+
+    /// How many shares of `bid` can be paid for at `fill_price` out of the
+    /// order's own reservation plus cash no other order has a claim on.
+    pub(super) fn affordable(
+        &self,
+        bid: &Order,
+        fill_price: i32,
+    ) -> u32 {
+        if fill_price <= 0 {
+            return u32::MAX;
         }
+        ((self.available_cash() + bid.notional()) / i64::from(fill_price)).clamp(0, i64::from(u32::MAX)) as u32
     }
 
-    pub(super) fn available_cash(&self) -> i32 {
-        self.cash - self.open_bids.iter().sum::<i32>()
+    pub(super) fn open_orders(&self) -> usize {
+        self.open_bids.len() + self.open_asks.len()
     }
 
     pub(super) fn available_shares(&self) -> usize {
-        self.shares.len().saturating_sub(self.open_asks.len())
+        let reserved = reserved(&self.open_asks, |a| i64::from(a.quantity)) as usize;
+        self.shares.len().saturating_sub(reserved)
     }
 
-    pub(super) fn net_worth(
-        &self,
-        current_price: i32,
-    ) -> i32 {
-        self.cash + (self.shares.len() as i32 * current_price)
+    /// Unlinks the remaining members of a group after one of them was removed
+    /// by the player. Returns whether the group still existed.
+    pub(super) fn dissolve_group(
+        &mut self,
+        group_id: GroupId,
+    ) -> bool {
+        if self.groups.remove(&group_id).is_none() {
+            return false;
+        }
+        for order in self.open_bids.iter_mut().chain(self.open_asks.iter_mut()) {
+            if order.group == Some(group_id) {
+                order.group = None;
+            }
+        }
+        true
+    }
+
+    /// Releases a group after the player canceled one of its members. A
+    /// bracket whose entry had partly filled arms its exits for the shares
+    /// bought; anything else is dissolved. Returns what became of the group,
+    /// or `None` if it no longer existed.
+    pub(super) fn release_group(
+        &mut self,
+        group_id: GroupId,
+    ) -> Option<GroupStatus> {
+        match self.groups.get(&group_id)?.pending_exits.filter(|exits| exits.filled > 0) {
+            Some(exits) => {
+                self.arm_exits(group_id, exits);
+                Some(GroupStatus::Armed)
+            }
+            None => self.dissolve_group(group_id).then_some(GroupStatus::Canceled),
+        }
+    }
+
+    /// Puts a bracket's exits on the book for the shares its entry bought,
+    /// leaving the group a plain OCO pair.
+    pub(super) fn arm_exits(
+        &mut self,
+        group_id: GroupId,
+        exits: BracketExits,
+    ) {
+        if let Some(group) = self.groups.get_mut(&group_id) {
+            group.pending_exits = None;
+        }
+        self.open_asks.push(Order {
+            group: Some(group_id),
+            ..Order::new(exits.take_profit, exits.filled)
+        });
+        self.open_asks.push(Order {
+            kind: OrderKind::Stop,
+            group: Some(group_id),
+            ..Order::new(exits.stop_loss, exits.filled)
+        });
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GameState {
     pub(super) phase: GamePhase,
-    config: GameConfig,
+    pub(super) config: GameConfig,
     pub(super) players: HashMap<PlayerId, PlayerState>,
     pub(super) player_tickers: HashMap<PlayerId, PlayerTicker>,
-    pub(super) ticks_remaining: u32,
+    /// Players in the order the game was created with, for `Correlation` lookups.
+    pub(super) seats: Vec<PlayerId>,
+    /// Game time played so far, advanced by the current interval on every tick.
+    pub(super) elapsed: Duration,
+    pub(super) tick_interval: Duration,
+    pub(super) next_group_id: u32,
+    pub(super) regime: Regime,
+    pub(super) index_price: i32,
+    pub(super) next_contract_id: u32,
+    pub(super) offers: HashMap<OfferId, TradeOffer>,
+    pub(super) next_offer_id: u32,
+    pub(super) market_makers: HashMap<PlayerId, MarketMaker>,
+    pub(super) scheduled_forces: Vec<ScheduledForce>,
+    /// Every tip issued so far, kept for the reveal at game end.
+    pub(super) insider_tips: Vec<InsiderTip>,
 }
 
 impl GameState {
@@ -58,15 +170,65 @@ impl GameState {
             GameAction::Countdown(remaining) => self.handle_countdown(remaining),
             GameAction::Start => self.handle_start(),
             GameAction::Tick => self.handle_price_tick(),
-            GameAction::Bid { player_id, bid_value } => self.handle_bid(player_id, bid_value),
-            GameAction::Ask { player_id, ask_value } => self.handle_ask(player_id, ask_value),
+            GameAction::Bid {
+                player_id,
+                bid_value,
+                quantity,
+            } => self.handle_bid(player_id, bid_value, quantity),
+            GameAction::Ask {
+                player_id,
+                ask_value,
+                quantity,
+            } => self.handle_ask(player_id, ask_value, quantity),
             GameAction::CancelBid { player_id, price } => self.handle_cancel_bid(player_id, price),
             GameAction::CancelAsk { player_id, price } => self.handle_cancel_ask(player_id, price),
+            GameAction::AmendOrder {
+                player_id,
+                side,
+                price,
+                new_price,
+                new_quantity,
+            } => self.handle_amend_order(player_id, side, price, new_price, new_quantity),
+            GameAction::CancelAll { player_id, side } => self.handle_cancel_all(player_id, side),
+            GameAction::PlaceOco {
+                player_id,
+                first,
+                second,
+            } => self.handle_place_oco(player_id, first, second),
+            GameAction::PlaceBracket {
+                player_id,
+                entry_price,
+                quantity,
+                take_profit,
+                stop_loss,
+            } => self.handle_place_bracket(player_id, entry_price, quantity, take_profit, stop_loss),
+            GameAction::Borrow { player_id, amount } => self.handle_borrow(player_id, amount),
+            GameAction::Repay { player_id, amount } => self.handle_repay(player_id, amount),
+            GameAction::TradeIndex {
+                player_id,
+                side,
+                quantity,
+            } => self.handle_trade_index(player_id, side, quantity),
+            GameAction::BuyOption { player_id, spec } => self.handle_trade_option(player_id, spec, false),
+            GameAction::WriteOption { player_id, spec } => self.handle_trade_option(player_id, spec, true),
+            GameAction::ProposeOffer {
+                seller,
+                buyer,
+                ticker,
+                quantity,
+                price,
+            } => self.handle_propose_offer(seller, buyer, ticker, quantity, price),
+            GameAction::RespondOffer {
+                player_id,
+                offer_id,
+                accept,
+            } => self.handle_respond_offer(player_id, offer_id, accept),
+            GameAction::ExpireOffer { offer_id } => self.handle_expire_offer(offer_id),
             GameAction::End => self.handle_game_end(),
         }
     }
 
-    fn require_phase(
+    pub(super) fn require_phase(
         &self,
         required: GamePhase,
         action: &'static str,
@@ -79,6 +241,104 @@ impl GameState {
         }
         Ok(())
     }
+
+    /// Fraction of `GameConfig::game_duration` played so far.
+    fn progress(&self) -> f32 {
+        self.elapsed.as_secs_f32() / self.config.game_duration.as_secs_f32()
+    }
+
+    /// Advances the regime chain and pushes the combined schedule and regime
+    /// volatility, plus market maker inventory pressure, into every ticker.
+    /// Returns the new regime if it changed.
+    fn update_volatility(&mut self) -> Option<Regime> {
+        let previous = self.regime;
+        let mut multiplier = self.config.volatility_schedule.multiplier_at(self.elapsed);
+        if let Some(regimes) = self.config.regimes {
+            self.regime = regimes.step(self.regime);
+            multiplier *= regimes.multiplier(self.regime);
+        }
+
+        let pressures: Vec<(PlayerId, f32)> = self.seats.iter().map(|&pid| (pid, self.inventory_pressure(pid))).collect();
+        for (player_id, pressure) in pressures {
+            if let Some(player_ticker) = self.player_tickers.get_mut(&player_id) {
+                player_ticker.ticker.set_ambient(MarketConditions {
+                    pressure,
+                    volatility: multiplier - 1.0,
+                });
+            }
+        }
+
+        (self.regime != previous).then_some(self.regime)
+    }
+
+    /// Feeds activity on `source`'s ticker to every ticker, scaled by
+    /// `GameConfig::correlation`.
+    pub(super) fn apply_impact(
+        &mut self,
+        source: PlayerId,
+        notional: f32,
+        impact: fn(&mut Ticker, f32),
+    ) {
+        let Some(source_seat) = self.seats.iter().position(|&pid| pid == source) else {
+            return;
+        };
+        for (target_seat, target) in self.seats.iter().enumerate() {
+            let weight = self.config.correlation.weight(source_seat, target_seat);
+            if weight == 0.0 {
+                continue;
+            }
+            if let Some(player_ticker) = self.player_tickers.get_mut(target) {
+                impact(&mut player_ticker.ticker, notional * weight);
+            }
+        }
+    }
+
+    /// Orders are accepted during continuous trading and the opening auction.
+    pub(super) fn require_trading(
+        &self,
+        action: &'static str,
+    ) -> Result<(), GameError> {
+        if !matches!(self.phase, GamePhase::Running | GamePhase::Auction) {
+            return Err(GameError::InvalidPhase {
+                action,
+                phase: self.phase.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks an order against the `OrderRules`, as if `pending` other new
+    /// orders had already been added alongside it.
+    pub(super) fn validate_order(
+        &self,
+        player_id: PlayerId,
+        price: i32,
+        quantity: u32,
+        pending: usize,
+    ) -> Result<(), GameError> {
+        let rules = &self.config.order_rules;
+        let state = self.players.get(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
+        let current_price = self.player_tickers.get(&player_id).map(|pt| pt.current_price).unwrap_or(0);
+
+        rules.check_price(price, current_price)?;
+        rules.check_quantity(quantity)?;
+        rules.check_open_orders(state.open_orders() + pending)
+    }
+
+    pub(super) fn broadcast(
+        &self,
+        events: Vec<GameEvent>,
+    ) -> Vec<GameEffect> {
+        events
+            .into_iter()
+            .flat_map(|event| {
+                self.players.keys().map(move |&player_id| GameEffect::Notification {
+                    player_id,
+                    event: event.clone(),
+                })
+            })
+            .collect()
+    }
 }
 
 impl GameState {
@@ -88,31 +348,79 @@ impl GameState {
         config: GameConfig,
     ) -> Self {
         let starting_balance = config.starting_balance;
-        let tick_count = (config.game_duration.as_millis() / config.tick_interval.as_millis()) as u32;
+        let tick_interval = config.tick_curve.interval_at(0.0, config.tick_interval);
         let player_ids: Vec<PlayerId> = players.clone();
         let players = players
             .into_iter()
             .map(|pid| (pid, PlayerState::new(starting_balance)))
             .collect();
         let player_tickers = player_ids
-            .into_iter()
-            .map(|pid| (pid, PlayerTicker::new(config.max_price_delta, 0)))
+            .iter()
+            .map(|&pid| (pid, PlayerTicker::new(config.max_price_delta, 0)))
             .collect();
+        let market_makers = match config.market_maker {
+            Some(_) => player_ids.iter().map(|&pid| (pid, MarketMaker::default())).collect(),
+            None => HashMap::new(),
+        };
         Self {
             phase: GamePhase::Pending,
             config,
             players,
             player_tickers,
-            ticks_remaining: tick_count,
+            seats: player_ids,
+            elapsed: Duration::ZERO,
+            tick_interval,
+            next_group_id: 0,
+            regime: Regime::default(),
+            index_price: 0,
+            next_contract_id: 0,
+            offers: HashMap::new(),
+            next_offer_id: 0,
+            market_makers,
+            scheduled_forces: Vec::new(),
+            insider_tips: Vec::new(),
         }
     }
 
+    pub fn phase(&self) -> &GamePhase {
+        &self.phase
+    }
+
+    /// Players in seat order.
+    pub fn players(&self) -> &[PlayerId] {
+        &self.seats
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// Game time played since `Start`.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
     #[must_use]
     pub fn launch(
         players: Vec<PlayerId>,
         config: GameConfig,
     ) -> (Self, Vec<GameEffect>) {
-        let state = Self::new(players.clone(), config.clone());
+        let mut state = Self::new(players.clone(), config.clone());
+
+        // With an opening auction the countdown doubles as the order-entry window,
+        // so tickers need a reference price to validate orders against
+        let auction_notifications = if config.opening_auction {
+            state.phase = GamePhase::Auction;
+            for player_ticker in state.player_tickers.values_mut() {
+                player_ticker.current_price = config.starting_price;
+            }
+            state.update_index_price();
+            state.broadcast(vec![GameEvent::AuctionOpened {
+                reference_price: config.starting_price,
+            }])
+        } else {
+            Vec::new()
+        };
 
         let countdown_seconds = config.countdown_duration.as_secs() as u32;
 
@@ -129,7 +437,11 @@ impl GameState {
             action: GameAction::Start,
         };
 
-        let effects = countdown_effects.chain(std::iter::once(start_effect)).collect();
+        let effects = auction_notifications
+            .into_iter()
+            .chain(countdown_effects)
+            .chain(std::iter::once(start_effect))
+            .collect();
 
         (state, effects)
     }
@@ -149,15 +461,27 @@ impl GameState {
     }
 
     fn handle_start(&mut self) -> Result<Vec<GameEffect>, GameError> {
-        self.require_phase(GamePhase::Pending, "Start")?;
+        if self.phase != GamePhase::Auction {
+            self.require_phase(GamePhase::Pending, "Start")?;
+        }
+
+        let mut opening_events = if self.phase == GamePhase::Auction {
+            self.phase = GamePhase::Running;
+            self.uncross_opening_auction()
+        } else {
+            self.phase = GamePhase::Running;
 
-        self.phase = GamePhase::Running;
+            // Initialize all player tickers to starting price
+            let starting_price = self.config.starting_price;
+            for player_ticker in self.player_tickers.values_mut() {
+                player_ticker.current_price = starting_price;
+            }
+            Vec::new()
+        };
+        self.update_index_price();
+        opening_events.extend(self.refresh_quotes());
 
-        // Initialize all player tickers to starting price
         let starting_price = self.config.starting_price;
-        for player_ticker in self.player_tickers.values_mut() {
-            player_ticker.current_price = starting_price;
-        }
 
         let player_ids: Vec<PlayerId> = self.players.keys().copied().collect();
 
@@ -168,38 +492,94 @@ impl GameState {
                 starting_balance: self.config.starting_balance,
                 players: player_ids.clone(),
                 game_duration_secs: self.config.game_duration.as_secs(),
+                tick_interval_ms: self.tick_interval.as_millis() as u64,
             },
         });
 
         let first_tick_effect = GameEffect::DelayedAction {
-            delay: self.config.tick_interval,
+            delay: self.tick_interval,
             action: GameAction::Tick,
         };
 
-        Ok(started_notifications.chain(std::iter::once(first_tick_effect)).collect())
+        let opening_notifications = self.broadcast(opening_events);
+
+        Ok(started_notifications
+            .chain(opening_notifications)
+            .chain(std::iter::once(first_tick_effect))
+            .collect())
+    }
+
+    /// Opens every ticker at its auction clearing price and executes the
+    /// orders that cross it. Anything left over rests into continuous trading.
+    fn uncross_opening_auction(&mut self) -> Vec<GameEvent> {
+        let reference = self.config.starting_price;
+        let house_liquidity = self.config.liquidity_per_tick;
+
+        let mut events = Vec::new();
+        for (&player_id, player_ticker) in &mut self.player_tickers {
+            let price = self
+                .players
+                .get(&player_id)
+                .map(|s| clearing_price(&s.open_bids, &s.open_asks, reference, house_liquidity))
+                .unwrap_or(reference);
+            player_ticker.current_price = price;
+            events.push(GameEvent::AuctionCleared { player_id, price });
+        }
+
+        events.extend(self.resolve_bids());
+        events.extend(self.resolve_asks());
+        events
     }
 
     fn handle_price_tick(&mut self) -> Result<Vec<GameEffect>, GameError> {
         self.require_phase(GamePhase::Running, "PriceTick")?;
 
-        if self.ticks_remaining == 0 {
+        if self.elapsed >= self.config.game_duration {
             return Err(GameError::InvalidPhase {
                 action: "PriceTick",
                 phase: GamePhase::Ended,
             });
         }
 
-        self.ticks_remaining -= 1;
+        self.elapsed += self.tick_interval;
 
+        let mut tick_events: Vec<GameEvent> = self
+            .update_volatility()
+            .map(|regime| GameEvent::RegimeChanged { regime })
+            .into_iter()
+            .collect();
+
+        self.apply_scheduled_forces();
         for player_ticker in self.player_tickers.values_mut() {
             player_ticker.tick();
         }
-
-        let resolved_bids = self.resolve_bids();
-        let resolved_asks = self.resolve_asks();
+        self.update_index_price();
+
+        // The house fills at market first; orders it had no liquidity for can still cross the market maker's quotes
+        tick_events.extend(self.refresh_quotes());
+        tick_events.extend(self.resolve_bids());
+        tick_events.extend(self.resolve_asks());
+        tick_events.extend(self.trade_with_market_makers());
+        tick_events.extend(self.expire_options());
+        tick_events.extend(self.accrue_interest());
+
+        let next_interval = self.config.tick_curve.interval_at(self.progress(), self.config.tick_interval);
+        if next_interval != self.tick_interval {
+            self.tick_interval = next_interval;
+            tick_events.push(GameEvent::TickIntervalChanged {
+                interval_ms: next_interval.as_millis() as u64,
+            });
+        }
+        let tick_notifications = self.broadcast(tick_events);
+        let tip_notifications = self.issue_insider_tip();
 
         let player_ids: Vec<PlayerId> = self.players.keys().copied().collect();
-        let prices: Vec<(PlayerId, i32)> = self.player_tickers.iter().map(|(&pid, pt)| (pid, pt.current_price)).collect();
+        let prices: Vec<(PlayerId, i32)> = self
+            .player_tickers
+            .iter()
+            .map(|(&pid, pt)| (pid, pt.current_price))
+            .chain(std::iter::once((PlayerId::INDEX, self.index_price)))
+            .collect();
 
         let price_notifications = player_ids.iter().flat_map(|&notify_player| {
             prices.iter().map(move |&(ticker_owner, price)| GameEffect::Notification {
@@ -211,40 +591,20 @@ impl GameState {
             })
         });
 
-        let bid_notifications = resolved_bids.into_iter().flat_map(|(order_owner, bid_value)| {
-            player_ids.iter().map(move |&notify_player| GameEffect::Notification {
-                player_id: notify_player,
-                event: GameEvent::BidFilled {
-                    player_id: order_owner,
-                    bid_value,
-                },
-            })
-        });
-
-        let ask_notifications = resolved_asks.into_iter().flat_map(|(order_owner, ask_value)| {
-            player_ids.iter().map(move |&notify_player| GameEffect::Notification {
-                player_id: notify_player,
-                event: GameEvent::AskFilled {
-                    player_id: order_owner,
-                    ask_value,
-                },
-            })
-        });
-
-        let next_action = if self.ticks_remaining == 0 {
+        let next_action = if self.elapsed >= self.config.game_duration {
             GameAction::End
         } else {
             GameAction::Tick
         };
 
         let next_tick_effect = GameEffect::DelayedAction {
-            delay: self.config.tick_interval,
+            delay: self.tick_interval,
             action: next_action,
         };
 
         let effects: Vec<GameEffect> = price_notifications
-            .chain(bid_notifications)
-            .chain(ask_notifications)
+            .chain(tick_notifications)
+            .chain(tip_notifications)
             .chain(std::iter::once(next_tick_effect))
             .collect();
 
@@ -255,93 +615,185 @@ impl GameState {
         self.require_phase(GamePhase::Running, "End")?;
         self.phase = GamePhase::Ended;
 
-        let final_balances: Vec<(PlayerId, i32)> = self
+        // Options still open expire with the game
+        let option_events = self.settle_options(|_| true);
+
+        let settlements: Vec<PlayerSettlement> = self
             .players
             .iter()
             .map(|(&player_id, state)| {
-                let player_price = self.player_tickers.get(&player_id).map(|pt| pt.current_price).unwrap_or(0);
-                (player_id, state.net_worth(player_price))
+                let final_price = self.player_tickers.get(&player_id).map(|pt| pt.current_price).unwrap_or(0);
+                self.settle(player_id, state, final_price)
             })
             .collect();
+        let final_balances: Vec<(PlayerId, i32)> = settlements.iter().map(|s| (s.player_id, s.final_balance)).collect();
 
-        Ok(self
-            .players
-            .keys()
-            .map(|&player_id| GameEffect::Notification {
-                player_id,
-                event: GameEvent::GameEnded {
-                    final_balances: final_balances.clone(),
-                },
-            })
-            .collect())
+        let mut events = option_events;
+        if !self.insider_tips.is_empty() {
+            events.push(GameEvent::InsiderTipsRevealed {
+                tips: self.insider_tips.clone(),
+            });
+        }
+        events.push(GameEvent::GameEnded {
+            final_balances,
+            settlements,
+        });
+        Ok(self.broadcast(events))
     }
 
-    pub(super) fn resolve_bids(&mut self) -> Vec<(PlayerId, i32)> {
+    /// Liquidates a player's remaining shares according to `GameConfig::settlement`.
+    /// Open orders are ignored; only cash and shares actually held count, less
+    /// any margin debt.
+    fn settle(
+        &self,
+        player_id: PlayerId,
+        state: &PlayerState,
+        final_price: i32,
+    ) -> PlayerSettlement {
+        let shares = state.shares.len() as u32;
+        let proceeds = self.config.settlement.proceeds(
+            shares,
+            final_price,
+            self.config.order_rules.tick_size,
+            self.config.liquidity_per_tick,
+        );
+        // Index positions, long or short, are closed out at the final index price
+        let index_value = i64::from(state.index_position) * i64::from(self.index_price);
+        // Rival shares are simply marked to their ticker's final price
+        let holdings_value: i64 = state
+            .holdings
+            .iter()
+            .map(|(ticker, &quantity)| notional(self.player_tickers.get(ticker).map_or(0, |pt| pt.current_price), quantity))
+            .sum();
+        let final_balance =
+            i64::from(state.cash) + proceeds + index_value + holdings_value - i64::from(state.financing.debt);
+        PlayerSettlement {
+            player_id,
+            cash: state.cash,
+            shares,
+            final_price,
+            settlement_price: if shares == 0 {
+                final_price
+            } else {
+                clamp_cash(proceeds / i64::from(shares))
+            },
+            proceeds: clamp_cash(proceeds),
+            index_position: state.index_position,
+            index_value: clamp_cash(index_value),
+            holdings_value: clamp_cash(holdings_value),
+            debt: state.financing.debt,
+            trades: state.trades,
+            final_balance: clamp_cash(final_balance),
+        }
+    }
+
+    /// Fills triggered bids in book order, up to `GameConfig::liquidity_per_tick`
+    /// shares per ticker. Whatever does not fit stays on the book for the next
+    /// tick. Fills cascade to any order group the bid belongs to.
+    pub(super) fn resolve_bids(&mut self) -> Vec<GameEvent> {
         let player_prices: HashMap<PlayerId, i32> =
             self.player_tickers.iter().map(|(&pid, pt)| (pid, pt.current_price)).collect();
+        let liquidity = self.config.liquidity_per_tick;
 
         let mut resolved = Vec::new();
         for (player_id, state) in &mut self.players {
             let current_price = player_prices.get(player_id).copied().unwrap_or(0);
-            let can_fill_bid = |bid: i32| bid >= current_price;
-
-            let filled_indices: Vec<usize> = state
-                .open_bids
-                .iter()
-                .enumerate()
-                .filter(|(_, bid)| can_fill_bid(**bid))
-                .map(|(i, _)| i)
-                .collect();
-
-            for i in filled_indices.into_iter().rev() {
-                let bid_value = state.open_bids.remove(i);
-                state.shares.push(current_price);
-                state.cash -= current_price;
-                resolved.push((*player_id, bid_value));
+            let mut budget = liquidity;
+            let mut triggered = Vec::new();
+
+            for i in 0..state.open_bids.len() {
+                if budget == 0 {
+                    break;
+                }
+                let order = state.open_bids[i];
+                if !order.bid_fillable(current_price) || order.group.is_some_and(|g| triggered.contains(&g)) {
+                    continue;
+                }
+                // Stops can trigger above their reserved price, so never dip into other orders' reservations
+                let filled = order.quantity.min(budget).min(state.affordable(&order, current_price));
+                let bid = &mut state.open_bids[i];
+                if filled == 0 {
+                    continue;
+                }
+                budget -= filled;
+                bid.quantity -= filled;
+                state.shares.extend(std::iter::repeat_n(current_price, filled as usize));
+                state.cash -= current_price * filled as i32;
+                let group = bid.group;
+                if let Some(g) = group
+                    && state.groups.get(&g).is_some_and(|grp| grp.pending_exits.is_none())
+                {
+                    // Whatever is left of the filled leg lives on unlinked
+                    bid.group = None;
+                    triggered.push(g);
+                }
+                state.trades += 1;
+                resolved.push(Fill {
+                    player_id: *player_id,
+                    order_price: bid.price,
+                    fill_price: current_price,
+                    quantity: filled,
+                    remaining: bid.quantity,
+                    group,
+                });
             }
+            state.open_bids.retain(|b| b.quantity > 0);
         }
 
-        for &(player_id, _) in &resolved {
-            let fill_price = player_prices.get(&player_id).copied().unwrap_or(0);
-            for player_ticker in self.player_tickers.values_mut() {
-                player_ticker.ticker.on_bid_filled(fill_price as f32);
-            }
+        for fill in &resolved {
+            let notional = (fill.fill_price * fill.quantity as i32) as f32;
+            self.apply_impact(fill.player_id, notional, Ticker::on_bid_filled);
         }
 
-        resolved
+        let mut events = Vec::new();
+        for fill in resolved {
+            events.push(GameEvent::BidFilled {
+                player_id: fill.player_id,
+                bid_value: fill.order_price,
+                quantity: fill.quantity,
+                remaining: fill.remaining,
+            });
+            events.extend(self.cascade_fill(&fill));
+        }
+        events
     }
 
     fn handle_bid(
         &mut self,
         player_id: PlayerId,
         bid_value: i32,
+        quantity: u32,
     ) -> Result<Vec<GameEffect>, GameError> {
-        self.require_phase(GamePhase::Running, "Bid")?;
+        self.require_trading("Bid")?;
+        self.validate_order(player_id, bid_value, quantity, 0)?;
 
+        let order = Order::new(bid_value, quantity);
         let state = self.players.get(&player_id);
         let available_player_balance = state.map(|s| s.available_cash()).unwrap_or(0);
 
-        if bid_value > available_player_balance {
+        if order.notional() > available_player_balance {
             return Err(GameError::InsufficientFunds {
                 available: available_player_balance,
-                required: bid_value,
+                required: order.notional(),
             });
         }
 
         if let Some(state) = self.players.get_mut(&player_id) {
-            state.open_bids.push(bid_value);
+            state.open_bids.push(order);
         }
 
-        for player_ticker in self.player_tickers.values_mut() {
-            player_ticker.ticker.on_bid_placed(bid_value as f32);
-        }
+        self.apply_impact(player_id, order.notional() as f32, Ticker::on_bid_placed);
 
         Ok(self
             .players
             .keys()
             .map(|&pid| GameEffect::Notification {
                 player_id: pid,
-                event: GameEvent::BidPlaced { player_id, bid_value },
+                event: GameEvent::BidPlaced {
+                    player_id,
+                    bid_value,
+                    quantity,
+                },
             })
             .collect())
     }
@@ -350,33 +802,38 @@ impl GameState {
         &mut self,
         player_id: PlayerId,
         ask_value: i32,
+        quantity: u32,
     ) -> Result<Vec<GameEffect>, GameError> {
-        self.require_phase(GamePhase::Running, "Ask")?;
+        self.require_trading("Ask")?;
+        self.validate_order(player_id, ask_value, quantity, 0)?;
 
+        let order = Order::new(ask_value, quantity);
         let state = self.players.get(&player_id);
         let player_shares_available = state.map(|s| s.available_shares()).unwrap_or(0);
 
-        if player_shares_available == 0 {
+        if player_shares_available < quantity as usize {
             return Err(GameError::InsufficientShares {
                 available: player_shares_available,
-                required: 1,
+                required: quantity as usize,
             });
         }
 
         if let Some(state) = self.players.get_mut(&player_id) {
-            state.open_asks.push(ask_value);
+            state.open_asks.push(order);
         }
 
-        for player_ticker in self.player_tickers.values_mut() {
-            player_ticker.ticker.on_ask_placed(ask_value as f32);
-        }
+        self.apply_impact(player_id, order.notional() as f32, Ticker::on_ask_placed);
 
         Ok(self
             .players
             .keys()
             .map(|&pid| GameEffect::Notification {
                 player_id: pid,
-                event: GameEvent::AskPlaced { player_id, ask_value },
+                event: GameEvent::AskPlaced {
+                    player_id,
+                    ask_value,
+                    quantity,
+                },
             })
             .collect())
     }
@@ -386,29 +843,33 @@ impl GameState {
         player_id: PlayerId,
         price: i32,
     ) -> Result<Vec<GameEffect>, GameError> {
-        self.require_phase(GamePhase::Running, "CancelBid")?;
+        self.require_trading("CancelBid")?;
 
         let state = self.players.get_mut(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
 
         let idx = state
             .open_bids
             .iter()
-            .position(|&b| b == price)
+            .position(|b| b.price == price)
             .ok_or(GameError::OrderNotFound {
                 order_type: "bid".to_string(),
                 price,
             })?;
 
-        state.open_bids.remove(idx);
+        let canceled = state.open_bids.remove(idx);
+        let released = canceled.group.and_then(|g| Some((g, state.release_group(g)?)));
 
-        Ok(self
-            .players
-            .keys()
-            .map(|&pid| GameEffect::Notification {
-                player_id: pid,
-                event: GameEvent::BidCanceled { player_id, price },
-            })
-            .collect())
+        let mut events = vec![GameEvent::BidCanceled {
+            player_id,
+            price,
+            quantity: canceled.quantity,
+        }];
+        events.extend(released.map(|(group_id, status)| GameEvent::OrderGroupUpdated {
+            player_id,
+            group_id,
+            status,
+        }));
+        Ok(self.broadcast(events))
     }
 
     fn handle_cancel_ask(
@@ -416,67 +877,225 @@ impl GameState {
         player_id: PlayerId,
         price: i32,
     ) -> Result<Vec<GameEffect>, GameError> {
-        self.require_phase(GamePhase::Running, "CancelAsk")?;
+        self.require_trading("CancelAsk")?;
 
         let state = self.players.get_mut(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
 
         let idx = state
             .open_asks
             .iter()
-            .position(|&a| a == price)
+            .position(|a| a.price == price)
             .ok_or(GameError::OrderNotFound {
                 order_type: "ask".to_string(),
                 price,
             })?;
 
-        state.open_asks.remove(idx);
+        let canceled = state.open_asks.remove(idx);
+        let dissolved = canceled.group.filter(|&g| state.dissolve_group(g));
+
+        let mut events = vec![GameEvent::AskCanceled {
+            player_id,
+            price,
+            quantity: canceled.quantity,
+        }];
+        events.extend(dissolved.map(|group_id| GameEvent::OrderGroupUpdated {
+            player_id,
+            group_id,
+            status: GroupStatus::Canceled,
+        }));
+        Ok(self.broadcast(events))
+    }
+
+    fn handle_amend_order(
+        &mut self,
+        player_id: PlayerId,
+        side: Side,
+        price: i32,
+        new_price: i32,
+        new_quantity: u32,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        self.require_trading("AmendOrder")?;
+
+        let current_price = self.player_tickers.get(&player_id).map(|pt| pt.current_price).unwrap_or(0);
+        self.config.order_rules.check_price(new_price, current_price)?;
+        self.config.order_rules.check_quantity(new_quantity)?;
+
+        let state = self.players.get_mut(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
+
+        let (orders, order_type) = match side {
+            Side::Bid => (&state.open_bids, "bid"),
+            Side::Ask => (&state.open_asks, "ask"),
+        };
+        let idx = orders.iter().position(|o| o.price == price).ok_or(GameError::OrderNotFound {
+            order_type: order_type.to_string(),
+            price,
+        })?;
+        let old = orders[idx];
+        let amended = Order {
+            price: new_price,
+            quantity: new_quantity,
+            ..old
+        };
+
+        // The old reservation is released by the amendment, so only the difference must be covered
+        match side {
+            Side::Bid => {
+                let available = state.available_cash() + old.notional();
+                if amended.notional() > available {
+                    return Err(GameError::InsufficientFunds {
+                        available,
+                        required: amended.notional(),
+                    });
+                }
+            }
+            Side::Ask => {
+                let available = state.available_shares() + old.quantity as usize;
+                if new_quantity as usize > available {
+                    return Err(GameError::InsufficientShares {
+                        available,
+                        required: new_quantity as usize,
+                    });
+                }
+            }
+        }
+
+        // Amend in place so the order keeps its position in the book
+        match side {
+            Side::Bid => state.open_bids[idx] = amended,
+            Side::Ask => state.open_asks[idx] = amended,
+        }
+
+        let impact = match side {
+            Side::Bid => Ticker::on_bid_placed,
+            Side::Ask => Ticker::on_ask_placed,
+        };
+        self.apply_impact(player_id, amended.notional() as f32, impact);
 
         Ok(self
             .players
             .keys()
             .map(|&pid| GameEffect::Notification {
                 player_id: pid,
-                event: GameEvent::AskCanceled { player_id, price },
+                event: GameEvent::OrderAmended {
+                    player_id,
+                    side,
+                    price,
+                    new_price,
+                    new_quantity,
+                },
             })
             .collect())
     }
 
-    pub(super) fn resolve_asks(&mut self) -> Vec<(PlayerId, i32)> {
+    fn handle_cancel_all(
+        &mut self,
+        player_id: PlayerId,
+        side: Option<Side>,
+    ) -> Result<Vec<GameEffect>, GameError> {
+        self.require_trading("CancelAll")?;
+
+        let state = self.players.get_mut(&player_id).ok_or(GameError::PlayerNotFound(player_id))?;
+
+        let canceled_bids = if side != Some(Side::Ask) {
+            std::mem::take(&mut state.open_bids)
+        } else {
+            Vec::new()
+        };
+        let canceled_asks = if side != Some(Side::Bid) {
+            std::mem::take(&mut state.open_asks)
+        } else {
+            Vec::new()
+        };
+
+        let mut released: Vec<(GroupId, GroupStatus)> = Vec::new();
+        for group_id in canceled_bids.iter().chain(&canceled_asks).filter_map(|o| o.group) {
+            // Exits armed while canceling every ask would not be canceled with them
+            let status = if side == Some(Side::Bid) {
+                state.release_group(group_id)
+            } else {
+                state.dissolve_group(group_id).then_some(GroupStatus::Canceled)
+            };
+            released.extend(status.map(|status| (group_id, status)));
+        }
+
+        let as_pairs = |orders: Vec<Order>| orders.into_iter().map(|o| (o.price, o.quantity)).collect::<Vec<_>>();
+        let mut events = vec![GameEvent::OrdersCanceled {
+            player_id,
+            bids: as_pairs(canceled_bids),
+            asks: as_pairs(canceled_asks),
+        }];
+        events.extend(released.into_iter().map(|(group_id, status)| GameEvent::OrderGroupUpdated {
+            player_id,
+            group_id,
+            status,
+        }));
+        Ok(self.broadcast(events))
+    }
+
+    /// Mirror of `resolve_bids` for triggered asks.
+    pub(super) fn resolve_asks(&mut self) -> Vec<GameEvent> {
         let player_prices: HashMap<PlayerId, i32> =
             self.player_tickers.iter().map(|(&pid, pt)| (pid, pt.current_price)).collect();
+        let liquidity = self.config.liquidity_per_tick;
 
         let mut resolved = Vec::new();
 
         for (player_id, state) in &mut self.players {
             let current_price = player_prices.get(player_id).copied().unwrap_or(0);
-            let can_resolve_ask = |ask: i32| ask <= current_price;
-
-            let filled_indices: Vec<usize> = state
-                .open_asks
-                .iter()
-                .enumerate()
-                .filter(|(_, ask)| can_resolve_ask(**ask))
-                .map(|(i, _)| i)
-                .collect();
-
-            for i in filled_indices.into_iter().rev() {
-                let ask_value = state.open_asks.remove(i);
-                if !state.shares.is_empty() {
-                    state.shares.pop();
+            let mut budget = liquidity;
+            let mut triggered = Vec::new();
+
+            for ask in state.open_asks.iter_mut().filter(|a| a.ask_fillable(current_price)) {
+                if budget == 0 {
+                    break;
+                }
+                if ask.group.is_some_and(|g| triggered.contains(&g)) {
+                    continue;
                 }
-                state.cash += current_price;
-                resolved.push((*player_id, ask_value));
+                let filled = ask.quantity.min(budget).min(state.shares.len() as u32);
+                if filled == 0 {
+                    continue;
+                }
+                budget -= filled;
+                ask.quantity -= filled;
+                state.shares.truncate(state.shares.len() - filled as usize);
+                state.cash += current_price * filled as i32;
+                let group = ask.group;
+                if let Some(g) = group
+                    && state.groups.get(&g).is_some_and(|grp| grp.pending_exits.is_none())
+                {
+                    ask.group = None;
+                    triggered.push(g);
+                }
+                state.trades += 1;
+                resolved.push(Fill {
+                    player_id: *player_id,
+                    order_price: ask.price,
+                    fill_price: current_price,
+                    quantity: filled,
+                    remaining: ask.quantity,
+                    group,
+                });
             }
+            state.open_asks.retain(|a| a.quantity > 0);
         }
 
-        for &(player_id, _) in &resolved {
-            let fill_price = player_prices.get(&player_id).copied().unwrap_or(0);
-            for player_ticker in self.player_tickers.values_mut() {
-                player_ticker.ticker.on_ask_filled(fill_price as f32);
-            }
+        for fill in &resolved {
+            let notional = (fill.fill_price * fill.quantity as i32) as f32;
+            self.apply_impact(fill.player_id, notional, Ticker::on_ask_filled);
         }
 
-        resolved
+        let mut events = Vec::new();
+        for fill in resolved {
+            events.push(GameEvent::AskFilled {
+                player_id: fill.player_id,
+                ask_value: fill.order_price,
+                quantity: fill.quantity,
+                remaining: fill.remaining,
+            });
+            events.extend(self.cascade_fill(&fill));
+        }
+        events
     }
 
     #[cfg(test)]
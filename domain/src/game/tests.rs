@@ -4,7 +4,7 @@ use crate::*;
 
 #[derive(Default, Clone)]
 struct ExpectedPlayer {
-    cash: Option<i32>,
+    cash: Option<i64>,
     shares: Option<usize>,
     bids: Option<usize>,
     asks: Option<usize>,
@@ -17,7 +17,7 @@ fn player() -> ExpectedPlayer {
 impl ExpectedPlayer {
     fn cash(
         mut self,
-        cash: i32,
+        cash: i64,
     ) -> Self {
         self.cash = Some(cash);
         self
@@ -51,9 +51,24 @@ impl ExpectedPlayer {
 #[derive(Debug, Clone, PartialEq)]
 enum ExpectedOutcome {
     Ok,
-    InsufficientFunds { available: i32, required: i32 },
+    InsufficientFunds { available: i64, required: i64 },
     InsufficientShares { available: usize, required: usize },
     InvalidPhase { action: &'static str },
+    NonPositivePrice { price: i32 },
+    OffTick { price: i32 },
+    OutsidePriceBand { min: i32, max: i32 },
+    TooManyOpenOrders { limit: usize },
+    OrderNotFound { price: i32 },
+    ZeroQuantity,
+    QuantityTooLarge { max: u32 },
+    InvalidBracket,
+    BorrowingDisabled,
+    LeverageLimit { max_debt: i32 },
+    IndexPositionLimit { position: i64 },
+    OptionsDisabled,
+    StrikeOutOfRange { min: i32, max: i32 },
+    OfferNotFound,
+    TooManyOpenOffers { limit: usize },
 }
 
 struct TestHarness {
@@ -64,8 +79,15 @@ struct TestHarness {
 
 impl TestHarness {
     fn new(num_players: usize) -> Self {
+        Self::with_config(num_players, test_config())
+    }
+
+    fn with_config(
+        num_players: usize,
+        config: GameConfig,
+    ) -> Self {
         let players: Vec<PlayerId> = (0..num_players).map(|_| PlayerId(uuid::Uuid::new_v4())).collect();
-        let game = GameState::new(players.clone(), test_config());
+        let game = GameState::new(players.clone(), config);
         Self {
             game,
             players,
@@ -73,6 +95,20 @@ impl TestHarness {
         }
     }
 
+    /// Goes through `GameState::launch`, keeping the launch effects as the last result.
+    fn launched(
+        num_players: usize,
+        config: GameConfig,
+    ) -> Self {
+        let players: Vec<PlayerId> = (0..num_players).map(|_| PlayerId(uuid::Uuid::new_v4())).collect();
+        let (game, effects) = GameState::launch(players.clone(), config);
+        Self {
+            game,
+            players,
+            last_result: Ok(effects),
+        }
+    }
+
     fn at_price(
         mut self,
         price: i32,
@@ -80,6 +116,7 @@ impl TestHarness {
         for player_ticker in self.game.player_tickers.values_mut() {
             player_ticker.current_price = price;
         }
+        self.game.update_index_price();
         self.game.phase = GamePhase::Running;
         self
     }
@@ -92,11 +129,21 @@ impl TestHarness {
         &mut self,
         player_idx: usize,
         value: i32,
+    ) -> &mut Self {
+        self.bid_qty(player_idx, value, 1)
+    }
+
+    fn bid_qty(
+        &mut self,
+        player_idx: usize,
+        value: i32,
+        quantity: u32,
     ) -> &mut Self {
         let player_id = self.players[player_idx];
         self.last_result = self.game.process_action(GameAction::Bid {
             player_id,
             bid_value: value,
+            quantity,
         });
         self
     }
@@ -105,11 +152,169 @@ impl TestHarness {
         &mut self,
         player_idx: usize,
         value: i32,
+    ) -> &mut Self {
+        self.ask_qty(player_idx, value, 1)
+    }
+
+    fn ask_qty(
+        &mut self,
+        player_idx: usize,
+        value: i32,
+        quantity: u32,
     ) -> &mut Self {
         let player_id = self.players[player_idx];
         self.last_result = self.game.process_action(GameAction::Ask {
             player_id,
             ask_value: value,
+            quantity,
+        });
+        self
+    }
+
+    fn amend(
+        &mut self,
+        player_idx: usize,
+        side: Side,
+        price: i32,
+        new_price: i32,
+        new_quantity: u32,
+    ) -> &mut Self {
+        let player_id = self.players[player_idx];
+        self.last_result = self.game.process_action(GameAction::AmendOrder {
+            player_id,
+            side,
+            price,
+            new_price,
+            new_quantity,
+        });
+        self
+    }
+
+    fn cancel_all(
+        &mut self,
+        player_idx: usize,
+        side: Option<Side>,
+    ) -> &mut Self {
+        let player_id = self.players[player_idx];
+        self.last_result = self.game.process_action(GameAction::CancelAll { player_id, side });
+        self
+    }
+
+    fn borrow(
+        &mut self,
+        player_idx: usize,
+        amount: i32,
+    ) -> &mut Self {
+        let player_id = self.players[player_idx];
+        self.last_result = self.game.process_action(GameAction::Borrow { player_id, amount });
+        self
+    }
+
+    fn repay(
+        &mut self,
+        player_idx: usize,
+        amount: i32,
+    ) -> &mut Self {
+        let player_id = self.players[player_idx];
+        self.last_result = self.game.process_action(GameAction::Repay { player_id, amount });
+        self
+    }
+
+    fn trade_index(
+        &mut self,
+        player_idx: usize,
+        side: Side,
+        quantity: u32,
+    ) -> &mut Self {
+        let player_id = self.players[player_idx];
+        self.last_result = self.game.process_action(GameAction::TradeIndex {
+            player_id,
+            side,
+            quantity,
+        });
+        self
+    }
+
+    fn option(
+        &mut self,
+        player_idx: usize,
+        spec: OptionSpec,
+        written: bool,
+    ) -> &mut Self {
+        let player_id = self.players[player_idx];
+        let action = if written {
+            GameAction::WriteOption { player_id, spec }
+        } else {
+            GameAction::BuyOption { player_id, spec }
+        };
+        self.last_result = self.game.process_action(action);
+        self
+    }
+
+    /// Player `seller_idx` offers `quantity` of their own ticker to `buyer_idx`.
+    fn propose(
+        &mut self,
+        seller_idx: usize,
+        buyer_idx: usize,
+        quantity: u32,
+        price: i32,
+    ) -> &mut Self {
+        let seller = self.players[seller_idx];
+        self.last_result = self.game.process_action(GameAction::ProposeOffer {
+            seller,
+            buyer: self.players[buyer_idx],
+            ticker: seller,
+            quantity,
+            price,
+        });
+        self
+    }
+
+    fn respond(
+        &mut self,
+        player_idx: usize,
+        offer_id: OfferId,
+        accept: bool,
+    ) -> &mut Self {
+        let player_id = self.players[player_idx];
+        self.last_result = self.game.process_action(GameAction::RespondOffer {
+            player_id,
+            offer_id,
+            accept,
+        });
+        self
+    }
+
+    fn oco(
+        &mut self,
+        player_idx: usize,
+        first: OrderSpec,
+        second: OrderSpec,
+    ) -> &mut Self {
+        let player_id = self.players[player_idx];
+        self.last_result = self.game.process_action(GameAction::PlaceOco {
+            player_id,
+            first,
+            second,
+        });
+        self
+    }
+
+    fn bracket(
+        &mut self,
+        player_idx: usize,
+        entry_price: i32,
+        quantity: u32,
+        take_profit: i32,
+        stop_loss: i32,
+    ) -> &mut Self {
+        let player_id = self.players[player_idx];
+        self.last_result = self.game.process_action(GameAction::PlaceBracket {
+            player_id,
+            entry_price,
+            quantity,
+            take_profit,
+            stop_loss,
         });
         self
     }
@@ -136,6 +341,7 @@ impl TestHarness {
         for player_ticker in self.game.player_tickers.values_mut() {
             player_ticker.current_price = price;
         }
+        self.game.update_index_price();
         self
     }
 
@@ -227,6 +433,56 @@ impl TestHarness {
             (Err(GameError::InvalidPhase { action, .. }), ExpectedOutcome::InvalidPhase { action: exp_action }) => {
                 assert_eq!(*action, *exp_action, "InvalidPhase: action mismatch");
             }
+            (Err(GameError::NonPositivePrice { price }), ExpectedOutcome::NonPositivePrice { price: exp_price }) => {
+                assert_eq!(*price, *exp_price, "NonPositivePrice: price mismatch");
+            }
+            (Err(GameError::OffTick { price, .. }), ExpectedOutcome::OffTick { price: exp_price }) => {
+                assert_eq!(*price, *exp_price, "OffTick: price mismatch");
+            }
+            (
+                Err(GameError::OutsidePriceBand { min, max, .. }),
+                ExpectedOutcome::OutsidePriceBand {
+                    min: exp_min,
+                    max: exp_max,
+                },
+            ) => {
+                assert_eq!((*min, *max), (*exp_min, *exp_max), "OutsidePriceBand: band mismatch");
+            }
+            (Err(GameError::TooManyOpenOrders { limit, .. }), ExpectedOutcome::TooManyOpenOrders { limit: exp_limit }) => {
+                assert_eq!(*limit, *exp_limit, "TooManyOpenOrders: limit mismatch");
+            }
+            (Err(GameError::OrderNotFound { price, .. }), ExpectedOutcome::OrderNotFound { price: exp_price }) => {
+                assert_eq!(*price, *exp_price, "OrderNotFound: price mismatch");
+            }
+            (Err(GameError::ZeroQuantity), ExpectedOutcome::ZeroQuantity) => {}
+            (Err(GameError::QuantityTooLarge { max, .. }), ExpectedOutcome::QuantityTooLarge { max: exp_max }) => {
+                assert_eq!(*max, *exp_max, "QuantityTooLarge: max mismatch");
+            }
+            (Err(GameError::InvalidBracket { .. }), ExpectedOutcome::InvalidBracket) => {}
+            (Err(GameError::BorrowingDisabled), ExpectedOutcome::BorrowingDisabled) => {}
+            (Err(GameError::OptionsDisabled), ExpectedOutcome::OptionsDisabled) => {}
+            (Err(GameError::OfferNotFound(_)), ExpectedOutcome::OfferNotFound) => {}
+            (Err(GameError::TooManyOpenOffers { limit, .. }), ExpectedOutcome::TooManyOpenOffers { limit: exp_limit }) => {
+                assert_eq!(*limit, *exp_limit, "TooManyOpenOffers: limit mismatch");
+            }
+            (
+                Err(GameError::StrikeOutOfRange { min, max, .. }),
+                ExpectedOutcome::StrikeOutOfRange {
+                    min: exp_min,
+                    max: exp_max,
+                },
+            ) => {
+                assert_eq!((*min, *max), (*exp_min, *exp_max), "StrikeOutOfRange: range mismatch");
+            }
+            (
+                Err(GameError::IndexPositionLimit { position, .. }),
+                ExpectedOutcome::IndexPositionLimit { position: exp_position },
+            ) => {
+                assert_eq!(*position, *exp_position, "IndexPositionLimit: position mismatch");
+            }
+            (Err(GameError::LeverageLimit { max_debt, .. }), ExpectedOutcome::LeverageLimit { max_debt: exp_max }) => {
+                assert_eq!(*max_debt, *exp_max, "LeverageLimit: max_debt mismatch");
+            }
             _ => {
                 panic!("Outcome mismatch: expected {:?}, got {:?}", expected, self.last_result);
             }
@@ -240,6 +496,24 @@ impl TestHarness {
     }
 
     #[track_caller]
+    fn settlement(
+        &self,
+        player_idx: usize,
+    ) -> PlayerSettlement {
+        let player_id = self.players[player_idx];
+        let effects = self.last_result.as_ref().expect("last action failed");
+        effects
+            .iter()
+            .find_map(|e| match e {
+                GameEffect::Notification {
+                    event: GameEvent::GameEnded { settlements, .. },
+                    ..
+                } => settlements.iter().find(|s| s.player_id == player_id).cloned(),
+                _ => None,
+            })
+            .expect("no settlement for player")
+    }
+
     fn check_phase(
         &self,
         expected: GamePhase,
@@ -324,11 +598,52 @@ impl TestHarness {
 fn test_config() -> GameConfig {
     GameConfig {
         tick_interval: Duration::from_secs(1),
+        tick_curve: TickCurve::default(),
         game_duration: Duration::from_secs(10),
         max_price_delta: 10,
         starting_price: 50,
         countdown_duration: Duration::from_secs(3),
         starting_balance: 100,
+        liquidity_per_tick: 5,
+        order_rules: OrderRules {
+            tick_size: 1,
+            price_band_pct: None,
+            max_open_orders: 10,
+            max_quantity: 1000,
+        },
+        opening_auction: false,
+        settlement: Settlement::MarkToMarket,
+        correlation: Correlation::default(),
+        volatility_schedule: VolatilitySchedule::default(),
+        regimes: None,
+        interest: None,
+        index: IndexConfig::default(),
+        options: None,
+        offer_timeout: Duration::from_secs(5),
+        max_open_offers: 2,
+        market_maker: None,
+        insider: None,
+    }
+}
+
+fn leg(
+    side: Side,
+    kind: OrderKind,
+    price: i32,
+    quantity: u32,
+) -> OrderSpec {
+    OrderSpec {
+        side,
+        kind,
+        price,
+        quantity,
+    }
+}
+
+fn config_with_rules(order_rules: OrderRules) -> GameConfig {
+    GameConfig {
+        order_rules,
+        ..test_config()
     }
 }
 
@@ -491,7 +806,7 @@ fn test_price_stays_non_negative() {
     let mut t = TestHarness::new(1).at_price(0);
 
     // Run ticks until exhausted (10 ticks in test config)
-    while t.game.ticks_remaining > 0 {
+    while t.game.elapsed < t.game.config.game_duration {
         t.tick();
         // Check all players' prices stay non-negative
         for (&player_id, player_ticker) in &t.game.player_tickers {
@@ -506,17 +821,16 @@ fn test_price_stays_non_negative() {
 }
 
 #[test]
-fn test_tick_decrements_remaining() {
+fn test_tick_advances_elapsed() {
     let mut t = TestHarness::new(1).at_price(50);
-    let initial_ticks = t.game.ticks_remaining;
 
     t.tick();
     t.check_ok();
 
     assert_eq!(
-        t.game.ticks_remaining,
-        initial_ticks - 1,
-        "Tick should decrement ticks_remaining"
+        t.game.elapsed,
+        Duration::from_secs(1),
+        "Tick should advance elapsed by the tick interval"
     );
 }
 
@@ -524,7 +838,7 @@ fn test_tick_decrements_remaining() {
 fn test_tick_schedules_next_tick() {
     let mut t = TestHarness::new(1).at_price(50);
     // With 10 ticks remaining, should schedule another Tick
-    assert!(t.game.ticks_remaining > 1);
+    assert_eq!(t.game.elapsed, Duration::ZERO);
 
     t.tick();
     t.check_ok()
@@ -535,10 +849,10 @@ fn test_tick_schedules_next_tick() {
 fn test_final_tick_schedules_end() {
     let mut t = TestHarness::new(1).at_price(50);
     // Consume all but one tick
-    while t.game.ticks_remaining > 1 {
+    while t.game.elapsed + t.game.tick_interval < t.game.config.game_duration {
         t.tick();
     }
-    assert_eq!(t.game.ticks_remaining, 1);
+    assert_eq!(t.game.elapsed, Duration::from_secs(9));
 
     t.tick();
     t.check_ok().check_has_delayed_action(Duration::from_secs(1), GameAction::End);
@@ -548,7 +862,7 @@ fn test_final_tick_schedules_end() {
 fn test_tick_with_zero_remaining_fails() {
     let mut t = TestHarness::new(1).at_price(50);
     // Consume all ticks
-    while t.game.ticks_remaining > 0 {
+    while t.game.elapsed < t.game.config.game_duration {
         t.tick();
     }
 
@@ -556,3 +870,1522 @@ fn test_tick_with_zero_remaining_fails() {
     t.tick();
     t.check_outcome(ExpectedOutcome::InvalidPhase { action: "PriceTick" });
 }
+
+#[test]
+fn test_non_positive_prices_rejected() {
+    let mut t = TestHarness::new(1).at_price(50);
+
+    t.bid(0, -20);
+    t.check_outcome(ExpectedOutcome::NonPositivePrice { price: -20 });
+    t.check(0, player().cash(100).bids(0));
+
+    t.bid(0, 0);
+    t.check_outcome(ExpectedOutcome::NonPositivePrice { price: 0 });
+
+    t.bid(0, 50).resolve_bids();
+    t.ask(0, -5);
+    t.check_outcome(ExpectedOutcome::NonPositivePrice { price: -5 });
+    t.check(0, player().asks(0));
+}
+
+#[test]
+fn test_tick_size_enforced() {
+    let mut t = TestHarness::with_config(
+        1,
+        config_with_rules(OrderRules {
+            tick_size: 5,
+            ..OrderRules::default()
+        }),
+    )
+    .at_price(50);
+
+    t.bid(0, 47);
+    t.check_outcome(ExpectedOutcome::OffTick { price: 47 });
+
+    t.bid(0, 45);
+    t.check_ok().check(0, player().bids(1));
+}
+
+#[test]
+fn test_price_band_enforced() {
+    let mut t = TestHarness::with_config(
+        1,
+        config_with_rules(OrderRules {
+            price_band_pct: Some(50),
+            ..OrderRules::default()
+        }),
+    )
+    .at_price(50);
+
+    t.bid(0, 24);
+    t.check_outcome(ExpectedOutcome::OutsidePriceBand { min: 25, max: 75 });
+
+    t.bid(0, 76);
+    t.check_outcome(ExpectedOutcome::OutsidePriceBand { min: 25, max: 75 });
+
+    t.bid(0, 25).bid(0, 75);
+    t.check_ok().check(0, player().bids(2));
+}
+
+#[test]
+fn test_open_order_cap_enforced() {
+    let mut t = TestHarness::with_config(
+        1,
+        config_with_rules(OrderRules {
+            max_open_orders: 2,
+            price_band_pct: None,
+            ..OrderRules::default()
+        }),
+    )
+    .at_price(50);
+
+    t.bid(0, 10).bid(0, 10);
+    t.check_ok();
+
+    t.bid(0, 10);
+    t.check_outcome(ExpectedOutcome::TooManyOpenOrders { limit: 2 });
+    t.check(0, player().cash(80).bids(2));
+}
+
+#[test]
+fn test_amend_bid_keeps_position() {
+    let mut t = TestHarness::new(1).at_price(50);
+
+    t.bid(0, 20).bid(0, 30);
+    t.amend(0, Side::Bid, 20, 45, 1);
+    t.check_ok().check(0, player().cash(25).bids(2)).check_all_notified(|e| {
+        matches!(
+            e,
+            GameEvent::OrderAmended {
+                price: 20,
+                new_price: 45,
+                ..
+            }
+        )
+    });
+
+    let prices: Vec<i32> = t
+        .game
+        .get_player(t.players[0])
+        .unwrap()
+        .open_bids
+        .iter()
+        .map(|b| b.price)
+        .collect();
+    assert_eq!(prices, vec![45, 30], "amended bid should stay first in line");
+}
+
+#[test]
+fn test_amend_bid_checks_released_funds() {
+    let mut t = TestHarness::new(1).at_price(50);
+
+    t.bid(0, 60).bid(0, 30);
+
+    // 10 free plus the 60 released by the amendment
+    t.amend(0, Side::Bid, 60, 71, 1);
+    t.check_outcome(ExpectedOutcome::InsufficientFunds {
+        available: 70,
+        required: 71,
+    });
+
+    t.amend(0, Side::Bid, 60, 70, 1);
+    t.check_ok().check(0, player().cash(0).bids(2));
+}
+
+#[test]
+fn test_amend_missing_order() {
+    let mut t = TestHarness::new(1).at_price(50);
+
+    t.amend(0, Side::Ask, 60, 65, 1);
+    t.check_outcome(ExpectedOutcome::OrderNotFound { price: 60 });
+
+    t.bid(0, 40);
+    t.amend(0, Side::Bid, 40, 0, 1);
+    t.check_outcome(ExpectedOutcome::NonPositivePrice { price: 0 });
+    t.check(0, player().cash(60));
+}
+
+#[test]
+fn test_cancel_all() {
+    let mut t = TestHarness::new(2).at_price(50);
+
+    t.bid(0, 50).resolve_bids();
+    t.bid(0, 10).bid(0, 20).ask(0, 60);
+    t.check(0, player().bids(2).asks(1));
+
+    t.cancel_all(0, Some(Side::Bid));
+    t.check_ok().check(0, player().cash(50).bids(0).asks(1)).check_all_notified(
+        |e| matches!(e, GameEvent::OrdersCanceled { bids, asks, .. } if bids.len() == 2 && asks.is_empty()),
+    );
+
+    t.bid(0, 10);
+    t.cancel_all(0, None);
+    t.check_ok().check(0, player().cash(50).bids(0).asks(0));
+}
+
+#[test]
+fn test_bid_quantity_reserves_notional() {
+    let mut t = TestHarness::new(1).at_price(10);
+
+    t.bid_qty(0, 10, 8);
+    t.check_ok().check(0, player().cash(20).bids(1));
+
+    t.bid_qty(0, 10, 3);
+    t.check_outcome(ExpectedOutcome::InsufficientFunds {
+        available: 20,
+        required: 30,
+    });
+
+    t.bid_qty(0, 10, 0);
+    t.check_outcome(ExpectedOutcome::ZeroQuantity);
+
+    t.bid_qty(0, 10, 1001);
+    t.check_outcome(ExpectedOutcome::QuantityTooLarge { max: 1000 });
+    t.bid_qty(0, 10, u32::MAX);
+    t.check_outcome(ExpectedOutcome::QuantityTooLarge { max: 1000 });
+}
+
+#[test]
+fn test_huge_orders_cannot_overflow_notional() {
+    let mut t = TestHarness::with_config(
+        2,
+        config_with_rules(OrderRules {
+            price_band_pct: None,
+            max_quantity: u32::MAX,
+            ..OrderRules::default()
+        }),
+    )
+    .at_price(50);
+
+    t.bid_qty(0, 50, 30_000_000);
+    t.check_outcome(ExpectedOutcome::InsufficientFunds {
+        available: 100,
+        required: 1_500_000_000,
+    });
+    t.bid_qty(0, i32::MAX, u32::MAX);
+    t.check_outcome(ExpectedOutcome::InsufficientFunds {
+        available: 100,
+        required: i64::from(i32::MAX) * i64::from(u32::MAX),
+    });
+    t.check(0, player().cash(100).bids(0));
+}
+
+#[test]
+fn test_partial_fills_across_ticks() {
+    // Liquidity of 5 shares per tick in the test config
+    let mut t = TestHarness::new(1).at_price(10);
+
+    t.bid_qty(0, 10, 8);
+    let fills = t.game.resolve_bids();
+    assert!(
+        matches!(
+            fills.as_slice(),
+            [GameEvent::BidFilled {
+                quantity: 5,
+                remaining: 3,
+                ..
+            }]
+        ),
+        "expected a single partial fill, got {:?}",
+        fills
+    );
+    t.check(0, player().cash(20).shares(5).bids(1));
+
+    t.resolve_bids();
+    t.check(0, player().cash(20).shares(8).bids(0));
+
+    t.ask_qty(0, 10, 7);
+    t.check_ok().check(0, player().asks(1));
+    t.ask_qty(0, 10, 2);
+    t.check_outcome(ExpectedOutcome::InsufficientShares {
+        available: 1,
+        required: 2,
+    });
+
+    t.set_price(12).resolve_asks();
+    t.check(0, player().cash(80).shares(3).asks(1));
+    t.resolve_asks();
+    t.check(0, player().cash(104).shares(1).asks(0));
+}
+
+#[test]
+fn test_fill_notifications_report_quantities() {
+    let mut t = TestHarness::new(2).at_price(10);
+
+    t.bid_qty(0, 10, 7);
+    t.set_price(0).tick();
+    t.check_ok().check_all_notified(|e| {
+        matches!(
+            e,
+            GameEvent::BidFilled {
+                quantity: 5,
+                remaining: 2,
+                ..
+            }
+        )
+    });
+}
+
+#[test]
+fn test_amend_quantity() {
+    let mut t = TestHarness::new(1).at_price(10);
+
+    t.bid_qty(0, 10, 2);
+    t.amend(0, Side::Bid, 10, 10, 11);
+    t.check_outcome(ExpectedOutcome::InsufficientFunds {
+        available: 100,
+        required: 110,
+    });
+
+    t.amend(0, Side::Bid, 10, 9, 11);
+    t.check_ok().check(0, player().cash(1).bids(1));
+}
+
+#[test]
+fn test_oco_fill_cancels_sibling() {
+    let mut t = TestHarness::new(1).at_price(50);
+
+    t.bid_qty(0, 50, 2).resolve_bids();
+
+    // Take profit at 60 or stop out at 40, sharing the same two shares
+    t.oco(
+        0,
+        leg(Side::Ask, OrderKind::Limit, 60, 2),
+        leg(Side::Ask, OrderKind::Stop, 40, 2),
+    );
+    t.check_ok().check(0, player().shares(2).asks(2)).check_all_notified(|e| {
+        matches!(
+            e,
+            GameEvent::OrderGroupUpdated {
+                status: GroupStatus::Placed { .. },
+                ..
+            }
+        )
+    });
+
+    // Nothing triggers between the legs
+    t.set_price(45).resolve_asks();
+    t.check(0, player().shares(2).asks(2));
+
+    let events = t.set_price(38).game.resolve_asks();
+    assert!(events.iter().any(|e| matches!(e, GameEvent::AskCanceled { price: 60, .. })));
+    assert!(events.iter().any(|e| matches!(
+        e,
+        GameEvent::OrderGroupUpdated {
+            status: GroupStatus::Triggered,
+            ..
+        }
+    )));
+    t.check(0, player().cash(76).shares(0).asks(0));
+}
+
+#[test]
+fn test_bracket_arms_after_entry_fills() {
+    let mut t = TestHarness::new(1).at_price(10);
+
+    t.bracket(0, 10, 7, 14, 8);
+    t.check_ok().check(0, player().cash(30).bids(1).asks(0));
+
+    // Liquidity only covers 5 of the 7 shares, so exits stay pending
+    t.resolve_bids();
+    t.check(0, player().shares(5).bids(1).asks(0));
+
+    let events = t.game.resolve_bids();
+    assert!(events.iter().any(|e| matches!(
+        e,
+        GameEvent::OrderGroupUpdated {
+            status: GroupStatus::Armed,
+            ..
+        }
+    )));
+    t.check(0, player().shares(7).bids(0).asks(2));
+
+    // Take profit hits; the stop is canceled even though only part of the position sold
+    t.set_price(15).resolve_asks();
+    t.check(0, player().shares(2).asks(1));
+    let remaining = &t.game.get_player(t.players[0]).unwrap().open_asks;
+    assert_eq!(remaining[0].kind, OrderKind::Limit);
+    assert_eq!(remaining[0].group, None);
+}
+
+#[test]
+fn test_bracket_arms_for_what_filled_when_the_rest_is_canceled() {
+    let mut t = TestHarness::new(1).at_price(10);
+    t.bracket(0, 10, 7, 14, 8);
+    t.resolve_bids();
+    t.check(0, player().shares(5).bids(1).asks(0));
+
+    let effects = t
+        .game
+        .process_action(GameAction::CancelBid {
+            player_id: t.players[0],
+            price: 10,
+        })
+        .unwrap();
+    assert!(effects.iter().any(|e| matches!(
+        e,
+        GameEffect::Notification {
+            event: GameEvent::OrderGroupUpdated {
+                status: GroupStatus::Armed,
+                ..
+            },
+            ..
+        }
+    )));
+    t.check(0, player().shares(5).bids(0).asks(2));
+    let exits = &t.game.get_player(t.players[0]).unwrap().open_asks;
+    assert!(exits.iter().all(|o| o.quantity == 5 && o.group.is_some()));
+
+    // The stop protects the shares that were bought
+    t.set_price(8).resolve_asks();
+    t.check(0, player().shares(0).asks(0));
+}
+
+#[test]
+fn test_unfilled_bracket_is_simply_canceled() {
+    let mut t = TestHarness::new(1).at_price(10);
+    t.bracket(0, 9, 3, 14, 8);
+    t.cancel_all(0, Some(Side::Bid)).check_ok();
+    t.check(0, player().cash(100).bids(0).asks(0));
+    assert!(t.game.get_player(t.players[0]).unwrap().groups.is_empty());
+}
+
+#[test]
+fn test_bracket_rejects_inverted_exits() {
+    let mut t = TestHarness::new(1).at_price(50);
+
+    t.bracket(0, 45, 1, 40, 60);
+    t.check_outcome(ExpectedOutcome::InvalidBracket);
+    t.check(0, player().cash(100).bids(0));
+}
+
+#[test]
+fn test_canceling_leg_dissolves_group() {
+    let mut t = TestHarness::new(1).at_price(50);
+
+    t.oco(
+        0,
+        leg(Side::Bid, OrderKind::Limit, 40, 1),
+        leg(Side::Bid, OrderKind::Stop, 60, 1),
+    );
+    // Only the larger leg is reserved
+    t.check(0, player().cash(40).bids(2));
+
+    t.game
+        .process_action(GameAction::CancelBid {
+            player_id: t.players[0],
+            price: 60,
+        })
+        .unwrap();
+    t.check(0, player().cash(60).bids(1));
+    assert!(t.game.get_player(t.players[0]).unwrap().groups.is_empty());
+
+    // The surviving leg is an ordinary bid again
+    t.set_price(40).resolve_bids();
+    t.check(0, player().shares(1).bids(0));
+}
+
+#[test]
+fn test_triggered_stop_leaves_other_reservations_alone() {
+    let mut t = TestHarness::new(1).at_price(10);
+
+    t.oco(
+        0,
+        leg(Side::Bid, OrderKind::Stop, 12, 2),
+        leg(Side::Bid, OrderKind::Limit, 5, 1),
+    );
+    t.bid_qty(0, 38, 2);
+    t.check_ok().check(0, player().cash(0).bids(3));
+
+    // The stop triggers first but could only pay for itself out of the 38s' reservation
+    t.set_price(30).resolve_bids();
+    t.check(0, player().cash(16).shares(2).bids(2));
+}
+
+fn auction_config() -> GameConfig {
+    GameConfig {
+        starting_price: 10,
+        opening_auction: true,
+        ..test_config()
+    }
+}
+
+#[test]
+fn test_opening_auction_discovers_price() {
+    let mut t = TestHarness::launched(2, auction_config());
+    t.check_phase(GamePhase::Auction)
+        .check_all_notified(|e| matches!(e, GameEvent::AuctionOpened { reference_price: 10 }));
+
+    // More demand than the house will absorb pushes the open up to the bid
+    t.bid_qty(0, 12, 7);
+    t.check_ok();
+
+    t.start();
+    let bidder = t.players[0];
+    t.check_ok()
+        .check_phase(GamePhase::Running)
+        .check_all_notified(|e| matches!(e, GameEvent::AuctionCleared { player_id, price: 12 } if *player_id == bidder));
+    assert_eq!(t.game.player_tickers[&bidder].current_price, 12);
+    assert_eq!(t.game.player_tickers[&t.players[1]].current_price, 10);
+    // Liquidity caps the opening fill; the rest keeps resting
+    t.check(0, player().cash(16).shares(5).bids(1));
+}
+
+#[test]
+fn test_opening_auction_absorbed_demand_opens_at_reference() {
+    let mut t = TestHarness::launched(1, auction_config());
+
+    t.bid_qty(0, 12, 3).bid_qty(0, 5, 2);
+    t.start();
+    t.check_ok().check_price(10);
+    t.check(0, player().cash(60).shares(3).bids(1));
+}
+
+#[test]
+fn test_orders_rejected_before_start_without_auction() {
+    let mut t = TestHarness::launched(1, test_config());
+    t.check_phase(GamePhase::Pending);
+
+    t.bid(0, 50);
+    t.check_outcome(ExpectedOutcome::InvalidPhase { action: "Bid" });
+}
+
+/// Ends a game holding 5 shares bought at 10, with 50 cash left.
+fn settle_five_shares(settlement: Settlement) -> PlayerSettlement {
+    let config = GameConfig {
+        liquidity_per_tick: 2,
+        settlement,
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(1, config).at_price(10);
+    t.bid_qty(0, 10, 5);
+    t.resolve_bids().resolve_bids().resolve_bids();
+    t.check(0, player().cash(50).shares(5));
+
+    t.end();
+    t.check_ok();
+    t.settlement(0)
+}
+
+#[test]
+fn test_mark_to_market_settlement() {
+    let s = settle_five_shares(Settlement::MarkToMarket);
+    assert_eq!((s.settlement_price, s.proceeds, s.final_balance), (10, 50, 100));
+}
+
+#[test]
+fn test_closing_auction_settlement() {
+    // The house takes 2 shares per level, so 5 shares clear two ticks down
+    let s = settle_five_shares(Settlement::ClosingAuction);
+    assert_eq!((s.settlement_price, s.proceeds, s.final_balance), (8, 40, 90));
+}
+
+#[test]
+fn test_price_impact_settlement() {
+    // 10, 9.5, 9, 8.5, 8 rounded down per share
+    let s = settle_five_shares(Settlement::PriceImpact { bps_per_share: 500 });
+    assert_eq!((s.shares, s.proceeds, s.final_balance), (5, 44, 94));
+}
+
+#[test]
+fn test_settlement_proceeds_cannot_truncate() {
+    let price = i32::MAX / 10;
+    let mut t = TestHarness::new(1).at_price(price);
+    t.game.players.get_mut(&t.players[0]).unwrap().shares = vec![price; 100];
+
+    // Ten times what a cash value can hold is reported as the most it can
+    t.end().check_ok();
+    let s = t.settlement(0);
+    assert_eq!((s.settlement_price, s.proceeds, s.final_balance), (price, i32::MAX, i32::MAX));
+}
+
+#[test]
+fn test_correlation_limits_spillover() {
+    let config = GameConfig {
+        correlation: Correlation::Uniform { own: 1.0, rival: 0.0 },
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(2, config).at_price(50);
+    t.bid(0, 40);
+    t.check_ok();
+
+    let pressure = |idx: usize| t.game.player_tickers[&t.players[idx]].ticker.compute_conditions().pressure;
+    assert!(pressure(0) > 0.0, "own ticker should feel the bid");
+    assert_eq!(pressure(1), 0.0, "rival ticker should be untouched");
+}
+
+#[test]
+fn test_sector_correlation_weights() {
+    let correlation = Correlation::Sectors {
+        sectors: vec![0, 0, 1],
+        own: 1.0,
+        same_sector: 0.5,
+        other_sector: 0.1,
+    };
+    assert_eq!(correlation.weight(0, 0), 1.0);
+    assert_eq!(correlation.weight(0, 1), 0.5);
+    assert_eq!(correlation.weight(1, 2), 0.1);
+    // Seats beyond the sector list are on their own
+    assert_eq!(correlation.weight(3, 4), 0.1);
+}
+
+#[test]
+fn test_volatility_schedule_follows_game_time() {
+    let config = GameConfig {
+        volatility_schedule: VolatilitySchedule {
+            windows: vec![
+                VolatilityWindow {
+                    start: Duration::ZERO,
+                    multiplier: 0.5,
+                },
+                VolatilityWindow {
+                    start: Duration::from_secs(8),
+                    multiplier: 3.0,
+                },
+            ],
+        },
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(1, config).at_price(50);
+    let volatility = |t: &TestHarness| t.game.player_tickers[&t.players[0]].ticker.compute_conditions().volatility;
+
+    t.tick();
+    t.check_ok();
+    assert_eq!(volatility(&t), -0.5, "calm open");
+
+    for _ in 0..7 {
+        t.tick();
+    }
+    assert_eq!(volatility(&t), 2.0, "chaotic close");
+}
+
+#[test]
+fn test_config_rejects_zero_tick_intervals() {
+    let config = GameConfig {
+        tick_curve: TickCurve::accelerate_at_end(0.2, Duration::ZERO),
+        ..test_config()
+    };
+    assert!(matches!(config.validate(), Err(GameError::InvalidConfig(_))));
+
+    let config = GameConfig {
+        tick_interval: Duration::ZERO,
+        ..test_config()
+    };
+    assert!(matches!(config.validate(), Err(GameError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_config_rejects_zero_game_duration() {
+    let config = GameConfig {
+        game_duration: Duration::ZERO,
+        ..test_config()
+    };
+    assert!(matches!(config.validate(), Err(GameError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_config_rejects_bad_volatility_multipliers() {
+    assert!(test_config().validate().is_ok());
+    for multiplier in [f32::NAN, f32::INFINITY, -1.0] {
+        let config = GameConfig {
+            regimes: Some(RegimeSwitching {
+                turbulent_multiplier: multiplier,
+                ..RegimeSwitching::default()
+            }),
+            ..test_config()
+        };
+        assert!(matches!(config.validate(), Err(GameError::InvalidConfig(_))));
+    }
+
+    // A game that got one anyway ticks rather than panicking
+    let config = GameConfig {
+        volatility_schedule: VolatilitySchedule {
+            windows: vec![VolatilityWindow {
+                start: Duration::ZERO,
+                multiplier: -1.0,
+            }],
+        },
+        ..test_config()
+    };
+    assert!(matches!(config.validate(), Err(GameError::InvalidConfig(_))));
+    let mut t = TestHarness::with_config(1, config).at_price(50);
+    t.tick().check_ok();
+}
+
+#[test]
+fn test_regime_change_is_announced() {
+    let config = GameConfig {
+        regimes: Some(RegimeSwitching {
+            calm_to_turbulent: 1.0,
+            turbulent_to_calm: 0.0,
+            ..RegimeSwitching::default()
+        }),
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(2, config).at_price(50);
+
+    t.tick();
+    t.check_ok().check_all_notified(|e| {
+        matches!(
+            e,
+            GameEvent::RegimeChanged {
+                regime: Regime::Turbulent
+            }
+        )
+    });
+
+    // Staying in a regime is not announced again
+    t.tick();
+    let effects = t.last_result.as_ref().unwrap();
+    assert!(!effects.iter().any(|e| matches!(
+        e,
+        GameEffect::Notification {
+            event: GameEvent::RegimeChanged { .. },
+            ..
+        }
+    )));
+}
+
+#[test]
+fn test_tick_curve_accelerates_toward_end() {
+    let config = GameConfig {
+        tick_curve: TickCurve::accelerate_at_end(0.2, Duration::from_millis(500)),
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(2, config).at_price(50);
+
+    // 8 of 10 seconds played: the last 20% begins and the cadence doubles
+    for _ in 0..7 {
+        t.tick();
+    }
+    t.check_ok()
+        .check_has_delayed_action(Duration::from_secs(1), GameAction::Tick);
+    t.tick();
+    t.check_ok()
+        .check_all_notified(|e| matches!(e, GameEvent::TickIntervalChanged { interval_ms: 500 }))
+        .check_has_delayed_action(Duration::from_millis(500), GameAction::Tick);
+
+    // The remaining 2 seconds take 4 faster ticks, ending on game time
+    for _ in 0..3 {
+        t.tick();
+    }
+    t.check_ok()
+        .check_has_delayed_action(Duration::from_millis(500), GameAction::Tick);
+    t.tick();
+    t.check_ok()
+        .check_has_delayed_action(Duration::from_millis(500), GameAction::End);
+    assert_eq!(t.game.elapsed, Duration::from_secs(10));
+}
+
+fn interest_config(
+    cash_rate_bps: u32,
+    borrow_rate_bps: u32,
+) -> GameConfig {
+    GameConfig {
+        interest: Some(InterestModel {
+            cash_rate_bps,
+            borrow_rate_bps,
+            max_leverage_pct: 200,
+        }),
+        ..test_config()
+    }
+}
+
+#[test]
+fn test_borrowing_disabled_by_default() {
+    let mut t = TestHarness::new(1).at_price(50);
+    t.borrow(0, 10);
+    t.check_outcome(ExpectedOutcome::BorrowingDisabled);
+}
+
+#[test]
+fn test_borrow_up_to_leverage_limit() {
+    let mut t = TestHarness::with_config(1, interest_config(0, 0)).at_price(50);
+
+    // 2x leverage on 100 equity allows 100 of debt
+    t.borrow(0, 60);
+    t.check_ok().check_all_notified(|e| {
+        matches!(
+            e,
+            GameEvent::FinancingUpdated {
+                cash_delta: 60,
+                debt: 60,
+                ..
+            }
+        )
+    });
+    t.check(0, player().cash(160));
+
+    t.borrow(0, 50);
+    t.check_outcome(ExpectedOutcome::LeverageLimit { max_debt: 100 });
+    t.borrow(0, i32::MAX);
+    t.check_outcome(ExpectedOutcome::LeverageLimit { max_debt: 100 });
+
+    // Repaying more than is owed only clears the debt
+    t.repay(0, 100);
+    t.check_ok().check_all_notified(|e| {
+        matches!(
+            e,
+            GameEvent::FinancingUpdated {
+                cash_delta: -60,
+                debt: 0,
+                ..
+            }
+        )
+    });
+    t.check(0, player().cash(100));
+}
+
+#[test]
+fn test_interest_accrues_and_debt_reduces_final_balance() {
+    let mut t = TestHarness::with_config(1, interest_config(150, 200)).at_price(50);
+    t.borrow(0, 50);
+
+    // Tick 1: +1.5 on the 100 of own cash, +1.0 on debt; the half unit carries over
+    t.tick();
+    t.check_ok();
+    t.check(0, player().cash(151));
+    // Tick 2: +1.5 on 100 again plus 0.5 carried, +1.02 on debt
+    t.tick();
+    t.check(0, player().cash(153));
+
+    t.end();
+    let s = t.settlement(0);
+    assert_eq!((s.debt, s.final_balance), (52, 101));
+}
+
+#[test]
+fn test_borrowed_cash_earns_no_interest() {
+    let mut t = TestHarness::with_config(2, interest_config(100, 200)).at_price(50);
+    t.borrow(0, 100).check_ok();
+
+    // Both players earn 1 on their own 100; the borrower also owes 2 on the loan
+    t.tick();
+    t.check(0, player().cash(201)).check(1, player().cash(101));
+    let debt = t.game.get_player(t.players[0]).unwrap().financing.debt;
+    assert_eq!(debt, 102);
+}
+
+#[test]
+fn test_config_rejects_cash_rate_at_or_above_borrow_rate() {
+    for (cash_rate_bps, borrow_rate_bps) in [(3, 3), (5, 3)] {
+        let config = interest_config(cash_rate_bps, borrow_rate_bps);
+        assert!(matches!(config.validate(), Err(GameError::InvalidConfig(_))));
+    }
+    assert!(interest_config(1, 3).validate().is_ok());
+}
+
+#[test]
+fn test_leverage_limit_cannot_wrap() {
+    let config = GameConfig {
+        interest: Some(InterestModel {
+            cash_rate_bps: 0,
+            borrow_rate_bps: 1,
+            max_leverage_pct: u32::MAX,
+        }),
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(1, config).at_price(50);
+    t.borrow(0, 1_000_000);
+    t.check_ok().check(0, player().cash(1_000_100));
+}
+
+#[test]
+fn test_equity_counts_option_positions() {
+    let config = GameConfig {
+        interest: Some(InterestModel {
+            cash_rate_bps: 0,
+            borrow_rate_bps: 1,
+            max_leverage_pct: 200,
+        }),
+        ..options_harness().game.config
+    };
+    let mut t = TestHarness::with_config(1, config).at_price(50);
+    let call = option_spec(&t, OptionKind::Call, 45, None, 2);
+    t.option(0, call, false).check_ok();
+    let cash = t.game.get_player(t.players[0]).unwrap().cash;
+
+    // Two calls 5 in the money add 10 of equity on top of the cash left
+    t.borrow(0, i32::MAX);
+    t.check_outcome(ExpectedOutcome::LeverageLimit { max_debt: cash + 10 });
+}
+
+#[test]
+fn test_index_is_weighted_average_of_tickers() {
+    let config = GameConfig {
+        index: IndexConfig {
+            weights: vec![3, 1],
+            ..IndexConfig::default()
+        },
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(2, config).at_price(50);
+    let seats = t.game.seats.clone();
+    t.game.player_tickers.get_mut(&seats[0]).unwrap().current_price = 40;
+    t.game.player_tickers.get_mut(&seats[1]).unwrap().current_price = 80;
+    t.game.update_index_price();
+    assert_eq!(t.game.index_price, 50);
+
+    t.tick();
+    t.check_ok()
+        .check_all_notified(|e| matches!(e, GameEvent::PriceChanged { player_id, .. } if *player_id == PlayerId::INDEX));
+}
+
+#[test]
+fn test_index_trade_quantity_cannot_wrap() {
+    let config = GameConfig {
+        order_rules: OrderRules {
+            max_quantity: u32::MAX,
+            ..test_config().order_rules
+        },
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(1, config).at_price(50);
+
+    // Cast to i32 this would be -1, turning the buy into a short
+    t.trade_index(0, Side::Bid, u32::MAX);
+    t.check_outcome(ExpectedOutcome::IndexPositionLimit {
+        position: i64::from(u32::MAX),
+    });
+    t.check(0, player().cash(100));
+}
+
+#[test]
+fn test_index_long_and_short() {
+    let config = GameConfig {
+        index: IndexConfig {
+            weights: Vec::new(),
+            max_position: 3,
+        },
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(1, config).at_price(50);
+
+    t.trade_index(0, Side::Bid, 2);
+    t.check_ok().check_all_notified(|e| {
+        matches!(
+            e,
+            GameEvent::IndexTraded {
+                price: 50,
+                position: 2,
+                ..
+            }
+        )
+    });
+    t.check(0, player().cash(0));
+
+    // Selling through zero goes short, crediting the proceeds but holding them as margin
+    t.trade_index(0, Side::Ask, 5);
+    t.check_ok();
+    t.check(0, player().cash(100));
+
+    t.trade_index(0, Side::Ask, 1);
+    t.check_outcome(ExpectedOutcome::IndexPositionLimit { position: -4 });
+
+    // The short is closed out at the final index price
+    t.set_price(60).end();
+    let s = t.settlement(0);
+    assert_eq!((s.index_position, s.index_value, s.final_balance), (-3, -180, 70));
+}
+
+#[test]
+fn test_short_index_proceeds_are_held_as_margin() {
+    let mut t = TestHarness::new(1).at_price(50);
+
+    t.trade_index(0, Side::Ask, 2).check_ok();
+    assert_eq!(t.game.get_player(t.players[0]).unwrap().cash, 200);
+    t.check(0, player().cash(100));
+    t.bid_qty(0, 50, 3);
+    t.check_outcome(ExpectedOutcome::InsufficientFunds {
+        available: 100,
+        required: 150,
+    });
+
+    // Covering draws on the margin it releases
+    t.set_price(60).trade_index(0, Side::Bid, 1).check_ok();
+    t.check(0, player().cash(90));
+    assert_eq!(t.game.get_player(t.players[0]).unwrap().index_margin, 50);
+    t.trade_index(0, Side::Bid, 1).check_ok();
+    t.check(0, player().cash(80));
+    assert_eq!(t.game.get_player(t.players[0]).unwrap().index_margin, 0);
+}
+
+#[test]
+fn test_settlement_values_cannot_wrap() {
+    let mut t = TestHarness::new(2).at_price(50);
+    let rival = t.players[1];
+    t.game
+        .players
+        .get_mut(&t.players[0])
+        .unwrap()
+        .holdings
+        .insert(rival, u32::MAX);
+
+    t.end().check_ok();
+    let s = t.settlement(0);
+    assert_eq!((s.holdings_value, s.final_balance), (i32::MAX, i32::MAX));
+}
+
+fn options_harness() -> TestHarness {
+    options_harness_with_players(1).at_price(50)
+}
+
+fn options_harness_with_players(num_players: usize) -> TestHarness {
+    let config = GameConfig {
+        options: Some(OptionsConfig {
+            max_strike_distance_pct: 20,
+            spread_bps: 0,
+        }),
+        ..test_config()
+    };
+    TestHarness::with_config(num_players, config)
+}
+
+fn option_spec(
+    t: &TestHarness,
+    kind: OptionKind,
+    strike: i32,
+    expiry_ticks: Option<u32>,
+    quantity: u32,
+) -> OptionSpec {
+    OptionSpec {
+        underlying: t.players[0],
+        kind,
+        strike,
+        expiry_ticks,
+        quantity,
+    }
+}
+
+#[test]
+fn test_options_disabled_by_default() {
+    let mut t = TestHarness::new(1).at_price(50);
+    let spec = option_spec(&t, OptionKind::Call, 50, None, 1);
+    t.option(0, spec, false);
+    t.check_outcome(ExpectedOutcome::OptionsDisabled);
+}
+
+#[test]
+fn test_option_priced_from_volatility() {
+    let t = options_harness();
+    // Volatility 10 over 3 ticks of +/-10 uniform moves gives sigma = 10, and an
+    // at-the-money option is worth sigma / sqrt(2 pi)
+    for kind in [OptionKind::Call, OptionKind::Put] {
+        let fair = t.game.option_fair_value(&option_spec(&t, kind, 50, Some(3), 1)).unwrap();
+        assert!((fair - 3.989).abs() < 0.01, "{kind:?} priced at {fair}");
+    }
+}
+
+#[test]
+fn test_strike_must_be_near_the_money() {
+    let mut t = options_harness();
+    let spec = option_spec(&t, OptionKind::Call, 70, None, 1);
+    t.option(0, spec, false);
+    t.check_outcome(ExpectedOutcome::StrikeOutOfRange { min: 40, max: 60 });
+}
+
+#[test]
+fn test_bought_call_settles_at_expiry() {
+    let mut t = options_harness();
+    let spec = option_spec(&t, OptionKind::Call, 50, Some(2), 2);
+    t.option(0, spec, false);
+    t.check_ok()
+        .check_all_notified(|e| matches!(e, GameEvent::OptionTraded { premium: 6, .. }));
+    t.check(0, player().cash(94));
+
+    t.set_price(60);
+    assert!(t.game.expire_options().is_empty());
+    let events = t.game.expire_options();
+    assert!(matches!(events[..], [GameEvent::OptionSettled { cash_delta: 20, .. }]));
+    t.check(0, player().cash(114));
+}
+
+#[test]
+fn test_written_put_locks_margin_until_game_end() {
+    let mut t = options_harness();
+    let spec = option_spec(&t, OptionKind::Put, 50, None, 2);
+    t.option(0, spec, true);
+    t.check_ok();
+    // Premium for 10 ticks to game end received, strike times quantity locked
+    t.check(0, player().cash(14));
+
+    t.set_price(40).end();
+    t.check_ok()
+        .check_all_notified(|e| matches!(e, GameEvent::OptionSettled { cash_delta: -20, .. }));
+    assert_eq!(t.settlement(0).final_balance, 94);
+}
+
+#[test]
+fn test_written_call_owes_no_more_than_its_margin() {
+    let mut t = options_harness();
+    let spec = option_spec(&t, OptionKind::Call, 50, Some(1), 1);
+    t.option(0, spec, true).check_ok();
+    let premium = t.game.get_player(t.players[0]).unwrap().available_cash();
+
+    // Past twice the entry spot the call is worth more than the 100 locked against it
+    t.set_price(250);
+    let events = t.game.expire_options();
+    assert!(matches!(events[..], [GameEvent::OptionSettled { cash_delta: -100, .. }]));
+    assert_eq!(t.game.get_player(t.players[0]).unwrap().cash, premium as i32);
+    t.check(0, player().cash(premium));
+}
+
+fn proposed_offer(t: &TestHarness) -> OfferId {
+    let effects = t.last_result.as_ref().expect("last action failed");
+    effects
+        .iter()
+        .find_map(|e| match e {
+            GameEffect::Notification {
+                event: GameEvent::OfferProposed { offer, .. },
+                ..
+            } => Some(offer.offer_id),
+            _ => None,
+        })
+        .expect("no offer proposed")
+}
+
+fn offer_status(t: &TestHarness) -> Option<OfferStatus> {
+    let effects = t.last_result.as_ref().expect("last action failed");
+    effects.iter().find_map(|e| match e {
+        GameEffect::Notification {
+            event: GameEvent::OfferResolved { status, .. },
+            ..
+        } => Some(*status),
+        _ => None,
+    })
+}
+
+#[test]
+fn test_offer_requires_shares() {
+    let mut t = TestHarness::new(2).at_price(50);
+    t.propose(0, 1, 1, 50);
+    t.check_outcome(ExpectedOutcome::InsufficientShares {
+        available: 0,
+        required: 1,
+    });
+}
+
+#[test]
+fn test_open_offers_are_capped() {
+    let mut t = TestHarness::new(2).at_price(50);
+    t.bid(0, 50).resolve_bids();
+    t.propose(0, 1, 1, 45).check_ok();
+    t.propose(0, 1, 1, 46).check_ok();
+
+    t.propose(0, 1, 1, 47);
+    t.check_outcome(ExpectedOutcome::TooManyOpenOffers { limit: 2 });
+
+    // Answering one frees a slot
+    let offer_id = t.game.offers.keys().next().copied().unwrap();
+    t.respond(1, offer_id, false).check_ok();
+    t.propose(0, 1, 1, 47).check_ok();
+}
+
+#[test]
+fn test_offer_only_notifies_parties_and_schedules_expiry() {
+    let mut t = TestHarness::new(3).at_price(50);
+    t.bid(0, 50).resolve_bids();
+    t.propose(0, 1, 1, 45).check_ok();
+
+    let effects = t.last_result.as_ref().unwrap();
+    let notified: Vec<PlayerId> = effects
+        .iter()
+        .filter_map(|e| match e {
+            GameEffect::Notification { player_id, .. } => Some(*player_id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(notified, vec![t.players[0], t.players[1]]);
+    let offer_id = proposed_offer(&t);
+    assert!(effects.iter().any(|e| matches!(
+        e,
+        GameEffect::DelayedAction { delay, action: GameAction::ExpireOffer { offer_id: id } }
+            if *delay == Duration::from_secs(5) && *id == offer_id
+    )));
+}
+
+#[test]
+fn test_accepted_offer_transfers_shares_and_cash() {
+    let mut t = TestHarness::new(2).at_price(50);
+    t.bid_qty(0, 50, 2).resolve_bids();
+    t.check(0, player().cash(0).shares(2));
+
+    t.propose(0, 1, 2, 45).check_ok();
+    let offer_id = proposed_offer(&t);
+    t.respond(1, offer_id, true).check_ok();
+    assert_eq!(offer_status(&t), Some(OfferStatus::Accepted));
+    t.check(0, player().cash(90).shares(0));
+    t.check(1, player().cash(10).shares(0));
+
+    // The buyer's holding of the seller's ticker is marked to market at the end
+    t.end();
+    let settlement = t.settlement(1);
+    assert_eq!(settlement.holdings_value, 100);
+    assert_eq!(settlement.final_balance, 110);
+}
+
+#[test]
+fn test_only_buyer_can_respond() {
+    let mut t = TestHarness::new(3).at_price(50);
+    t.bid(0, 50).resolve_bids();
+    t.propose(0, 1, 1, 45);
+    let offer_id = proposed_offer(&t);
+    t.respond(2, offer_id, true);
+    t.check_outcome(ExpectedOutcome::OfferNotFound);
+}
+
+#[test]
+fn test_accepting_checks_buyer_funds() {
+    let mut t = TestHarness::new(2).at_price(50);
+    t.bid(0, 50).resolve_bids();
+    t.propose(0, 1, 1, 150);
+    let offer_id = proposed_offer(&t);
+    t.respond(1, offer_id, true);
+    t.check_outcome(ExpectedOutcome::InsufficientFunds {
+        available: 100,
+        required: 150,
+    });
+
+    // A failed acceptance leaves the offer open to reject
+    t.respond(1, offer_id, false).check_ok();
+    assert_eq!(offer_status(&t), Some(OfferStatus::Rejected));
+}
+
+#[test]
+fn test_offer_expires_unless_answered() {
+    let mut t = TestHarness::new(2).at_price(50);
+    t.bid(0, 50).resolve_bids();
+    t.propose(0, 1, 1, 45);
+    let offer_id = proposed_offer(&t);
+    t.last_result = t.game.process_action(GameAction::ExpireOffer { offer_id });
+    t.check_ok();
+    assert_eq!(offer_status(&t), Some(OfferStatus::Expired));
+    t.check(0, player().shares(1));
+
+    t.propose(0, 1, 1, 45);
+    let offer_id = proposed_offer(&t);
+    t.respond(1, offer_id, false);
+    t.last_result = t.game.process_action(GameAction::ExpireOffer { offer_id });
+    t.check_ok();
+    assert_eq!(offer_status(&t), None);
+}
+
+/// A market maker alone: the house provides no liquidity.
+fn market_maker_harness(max_inventory: u32) -> TestHarness {
+    let config = GameConfig {
+        liquidity_per_tick: 0,
+        market_maker: Some(MarketMakerConfig {
+            half_spread: 2,
+            quote_size: 3,
+            max_inventory,
+            pressure_per_share: 0.5,
+        }),
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(1, config).at_price(50);
+    t.game.refresh_quotes();
+    t
+}
+
+#[test]
+fn test_market_maker_quotes_on_start() {
+    let config = GameConfig {
+        market_maker: Some(MarketMakerConfig::default()),
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(2, config);
+    t.start().check_ok().check_all_notified(|e| {
+        matches!(
+            e,
+            GameEvent::QuoteUpdated {
+                quote: Quote {
+                    bid: Some(48),
+                    ask: Some(52)
+                },
+                inventory: 0,
+                ..
+            }
+        )
+    });
+}
+
+#[test]
+fn test_bid_crossing_quote_fills_at_ask() {
+    let mut t = market_maker_harness(15);
+    t.bid_qty(0, 52, 1).bid_qty(0, 47, 1).check_ok();
+
+    let events = t.game.trade_with_market_makers();
+    assert!(matches!(
+        events[..],
+        [GameEvent::BidFilled {
+            bid_value: 52,
+            quantity: 1,
+            remaining: 0,
+            ..
+        }]
+    ));
+    // The bid below the quote keeps resting, with its 47 still reserved
+    t.check(0, player().cash(48 - 47).shares(1).bids(1));
+    assert_eq!(t.game.market_makers[&t.players[0]].inventory, -1);
+}
+
+#[test]
+fn test_quote_size_limits_fills_per_tick() {
+    let mut t = market_maker_harness(15);
+    t.game.players.get_mut(&t.players[0]).unwrap().cash = 1000;
+    t.bid_qty(0, 52, 5);
+    t.game.trade_with_market_makers();
+    t.check(0, player().shares(3).bids(1));
+
+    t.game.refresh_quotes();
+    t.game.trade_with_market_makers();
+    t.check(0, player().shares(5).bids(0));
+}
+
+#[test]
+fn test_inventory_limit_pulls_quote_and_adds_pressure() {
+    let mut t = market_maker_harness(2);
+    t.game.players.get_mut(&t.players[0]).unwrap().cash = 1000;
+    t.bid_qty(0, 52, 2);
+    t.game.trade_with_market_makers();
+
+    // Short its whole limit, the market maker stops offering and leans bullish
+    let events = t.game.refresh_quotes();
+    assert!(matches!(
+        events[..],
+        [GameEvent::QuoteUpdated {
+            quote: Quote {
+                bid: Some(48),
+                ask: None
+            },
+            inventory: -2,
+            ..
+        }]
+    ));
+    assert_eq!(t.game.inventory_pressure(t.players[0]), 1.0);
+}
+
+#[test]
+fn test_ask_crossing_quote_fills_at_bid() {
+    let mut t = market_maker_harness(15);
+    t.game.players.get_mut(&t.players[0]).unwrap().shares.push(50);
+    t.ask(0, 45);
+    t.game.trade_with_market_makers();
+    // Sold at the bid of 48, not the 45 asked
+    t.check(0, player().cash(148).shares(0).asks(0));
+    assert_eq!(t.game.market_makers[&t.players[0]].inventory, 1);
+}
+
+#[test]
+fn test_crossing_bid_never_pays_more_than_the_house() {
+    let config = GameConfig {
+        market_maker: Some(MarketMakerConfig::default()),
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(1, config).at_price(50);
+    t.bid(0, 80).check_ok();
+
+    // The order crosses the fresh quote too, but the house has liquidity to spare
+    t.tick().check_ok();
+    let price = t.game.player_tickers[&t.players[0]].current_price;
+    assert_eq!(t.game.get_player(t.players[0]).unwrap().shares, vec![price]);
+    assert_eq!(t.game.market_makers[&t.players[0]].inventory, 0);
+}
+
+#[test]
+fn test_market_maker_takes_what_the_house_cannot() {
+    let config = GameConfig {
+        liquidity_per_tick: 2,
+        market_maker: Some(MarketMakerConfig::default()),
+        ..test_config()
+    };
+    let mut t = TestHarness::with_config(1, config).at_price(50);
+    t.game.players.get_mut(&t.players[0]).unwrap().cash = 1000;
+    t.bid_qty(0, 80, 4).check_ok();
+
+    t.tick().check_ok();
+    let price = t.game.player_tickers[&t.players[0]].current_price;
+    assert_eq!(
+        t.game.get_player(t.players[0]).unwrap().shares,
+        vec![price, price, price + 2, price + 2]
+    );
+    assert_eq!(t.game.market_makers[&t.players[0]].inventory, -2);
+}
+
+#[test]
+fn test_market_maker_fills_move_the_price() {
+    let mut t = market_maker_harness(15);
+    let volatility = |t: &TestHarness| t.game.player_tickers[&t.players[0]].ticker.compute_conditions().volatility;
+    assert_eq!(volatility(&t), 0.0);
+    t.bid_qty(0, 52, 1).check_ok();
+    t.game.trade_with_market_makers();
+    assert!(volatility(&t) > 0.0);
+}
+
+fn insider_harness(
+    num_players: usize,
+    false_tip_chance: f64,
+) -> TestHarness {
+    let config = GameConfig {
+        insider: Some(InsiderConfig {
+            tip_chance: 1.0,
+            min_lead_ticks: 2,
+            max_lead_ticks: 2,
+            lead_noise_ticks: 0,
+            false_tip_chance,
+            force_pressure: 0.5,
+            force_ticks: 3,
+        }),
+        ..test_config()
+    };
+    TestHarness::with_config(num_players, config).at_price(50)
+}
+
+#[test]
+fn test_insider_tip_reaches_only_its_recipient() {
+    let mut t = insider_harness(3, 0.0);
+    let effects = t.game.issue_insider_tip();
+    assert!(matches!(
+        effects[..],
+        [GameEffect::Notification {
+            event: GameEvent::InsiderTip { in_ticks: 2, .. },
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn test_genuine_tip_force_hits_after_lead() {
+    let mut t = insider_harness(1, 0.0);
+    t.game.issue_insider_tip();
+    let tip = t.game.insider_tips[0];
+    let pressure = |t: &TestHarness| t.game.player_tickers[&t.players[0]].ticker.compute_conditions().pressure;
+
+    t.game.apply_scheduled_forces();
+    assert_eq!(pressure(&t), 0.0);
+    t.game.apply_scheduled_forces();
+    let expected = match tip.direction {
+        Direction::Bullish => 0.5,
+        Direction::Bearish => -0.5,
+    };
+    assert_eq!(pressure(&t), expected);
+    assert_eq!(tip.actual_in_ticks, Some(2));
+}
+
+#[test]
+fn test_false_tip_schedules_nothing() {
+    let mut t = insider_harness(1, 1.0);
+    t.game.issue_insider_tip();
+    t.game.apply_scheduled_forces();
+    t.game.apply_scheduled_forces();
+    assert_eq!(t.game.player_tickers[&t.players[0]].ticker.compute_conditions().pressure, 0.0);
+    assert_eq!(t.game.insider_tips[0].actual_in_ticks, None);
+}
+
+#[test]
+fn test_tips_revealed_at_game_end() {
+    let mut t = insider_harness(2, 0.5);
+    t.game.issue_insider_tip();
+    t.game.issue_insider_tip();
+    t.end().check_ok();
+    t.check_all_notified(|e| matches!(e, GameEvent::InsiderTipsRevealed { tips } if tips.len() == 2));
+}
+
+#[test]
+fn test_state_survives_serialization() {
+    let mut t = TestHarness::new(2).at_price(50);
+    t.bid_qty(0, 45, 2).check_ok();
+    t.oco(
+        1,
+        leg(Side::Bid, OrderKind::Limit, 40, 1),
+        leg(Side::Bid, OrderKind::Stop, 60, 1),
+    )
+    .check_ok();
+    t.game.players.get_mut(&t.players[0]).unwrap().shares = vec![50; 3];
+    t.propose(0, 1, 2, 20).check_ok();
+    let offer_id = proposed_offer(&t);
+
+    let json = serde_json::to_string(&t.game).expect("state serializes");
+    t.game = serde_json::from_str(&json).expect("state deserializes");
+
+    t.check(0, player().cash(100 - 90).shares(3).bids(1));
+    t.check(1, player().bids(2));
+    assert_eq!(t.game.phase(), &GamePhase::Running);
+    assert_eq!(t.game.players[&t.players[1]].groups.len(), 1);
+    t.respond(1, offer_id, true).check_ok();
+    t.check(0, player().cash(10 + 40).shares(1));
+}
+
+#[test]
+fn test_settlement_counts_trades() {
+    let mut t = TestHarness::new(2).at_price(20);
+    t.bid(0, 20).resolve_bids();
+    t.trade_index(0, Side::Bid, 1).check_ok();
+    t.end().check_ok();
+    assert_eq!(t.settlement(0).trades, 2);
+    assert_eq!(t.settlement(1).trades, 0);
+}
+
+#[test]
+fn test_player_view_rebuilds_portfolio() {
+    let mut t = options_harness_with_players(2).at_price(20);
+    t.bid(0, 20).resolve_bids();
+    t.bid(0, 15).check_ok();
+    t.oco(
+        0,
+        leg(Side::Ask, OrderKind::Limit, 30, 1),
+        leg(Side::Ask, OrderKind::Stop, 15, 1),
+    );
+    t.check_ok();
+    t.bracket(0, 10, 2, 14, 8).check_ok();
+    let call = option_spec(&t, OptionKind::Call, 20, Some(3), 1);
+    t.option(0, call, false).check_ok();
+    let put = option_spec(&t, OptionKind::Put, 18, None, 1);
+    t.option(0, put, true).check_ok();
+
+    let view = t.game.player_view(t.players[0]).unwrap();
+    assert_eq!(view.phase, GamePhase::Running);
+    assert_eq!(view.prices, vec![(t.players[0], 20), (t.players[1], 20)]);
+    assert_eq!(view.cash, t.game.get_player(t.players[0]).unwrap().cash);
+    assert_eq!(view.shares, vec![20]);
+    assert_eq!(view.remaining_ms, 10_000);
+
+    let order = |price, quantity, kind, group| OpenOrder {
+        price,
+        quantity,
+        kind,
+        group,
+    };
+    let oco = view.open_asks[0].group;
+    let bracket = view.open_bids[1].group;
+    assert!(oco.is_some() && bracket.is_some() && oco != bracket);
+    assert_eq!(
+        view.open_bids,
+        vec![order(15, 1, OrderKind::Limit, None), order(10, 2, OrderKind::Limit, bracket)]
+    );
+    assert_eq!(
+        view.open_asks,
+        vec![order(30, 1, OrderKind::Limit, oco), order(15, 1, OrderKind::Stop, oco)]
+    );
+    assert_eq!(
+        view.pending_brackets,
+        vec![PendingBracket {
+            group: bracket.unwrap(),
+            take_profit: 14,
+            stop_loss: 8,
+            quantity: 2,
+        }]
+    );
+
+    assert_eq!(view.options.len(), 2);
+    let (bought, written) = (&view.options[0], &view.options[1]);
+    assert_eq!(
+        (bought.spec, bought.written, bought.ticks_left, bought.margin),
+        (call, false, Some(3), 0)
+    );
+    assert_eq!((written.spec, written.written, written.ticks_left), (put, true, None));
+    assert!(written.margin > 0);
+    assert_ne!(bought.contract_id, written.contract_id);
+
+    assert!(t.game.player_view(PlayerId::new()).is_none());
+}
@@ -1,10 +1,14 @@
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Ticker {
     base_volatility: i32,
     base_pressure: i32,
     forces: Vec<MarketForce>,
+    /// Background conditions set by the game (schedules, regimes) rather than
+    /// by order activity. They never decay.
+    ambient: MarketConditions,
 }
 
 impl Ticker {
@@ -13,14 +17,22 @@ impl Ticker {
             base_volatility,
             base_pressure: 0,
             forces: Vec::new(),
+            ambient: MarketConditions::default(),
         }
     }
 
+    /// Largest price move a single tick can produce before pressure.
+    pub fn effective_volatility(&self) -> i32 {
+        let conditions = self.compute_conditions();
+        self.base_volatility + (conditions.volatility * self.base_volatility as f32) as i32
+    }
+
     pub fn next_delta(&self) -> i32 {
         let mut rng = rand::thread_rng();
         let conditions = self.compute_conditions();
 
-        let effective_volatility = self.base_volatility + (conditions.volatility * self.base_volatility as f32) as i32;
+        // Forces can in principle cancel out more volatility than there is
+        let effective_volatility = self.effective_volatility().max(0);
         let effective_pressure = self.base_pressure + (conditions.pressure * self.base_volatility as f32) as i32;
 
         rng.gen_range(-effective_volatility..=effective_volatility) + effective_pressure
@@ -35,8 +47,15 @@ impl Ticker {
         self.forces.push(MarketForce::new(pressure, volatility, decay));
     }
 
+    pub fn set_ambient(
+        &mut self,
+        conditions: MarketConditions,
+    ) {
+        self.ambient = conditions;
+    }
+
     pub fn compute_conditions(&self) -> MarketConditions {
-        let mut conditions = MarketConditions::default();
+        let mut conditions = self.ambient.clone();
         for force in &self.forces {
             conditions.pressure += force.effective_pressure();
             conditions.volatility += force.effective_volatility();
@@ -92,13 +111,13 @@ impl Ticker {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct MarketConditions {
     pub pressure: f32,
     pub volatility: f32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Decay {
     Instant,
     Duration { remaining: u32 },
@@ -168,7 +187,7 @@ impl Decay {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MarketForce {
     pub pressure: f32,
     pub volatility: f32,
@@ -197,7 +216,7 @@ impl MarketForce {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PlayerTicker {
     pub ticker: Ticker,
     pub current_price: i32,
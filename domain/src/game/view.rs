@@ -0,0 +1,143 @@
+use serde::Serialize;
+
+use crate::PlayerId;
+
+use super::GamePhase;
+use super::offers::TradeOffer;
+use super::options::{ContractId, OptionSpec};
+use super::order::{GroupId, Order, OrderKind};
+use super::state::GameState;
+
+/// Everything one player needs to rebuild their view of a game part way
+/// through, such as after reconnecting.
+#[derive(Clone, Debug, Serialize)]
+pub struct PlayerView {
+    pub phase: GamePhase,
+    /// Current price of every ticker, in seat order.
+    pub prices: Vec<(PlayerId, i32)>,
+    pub index_price: i32,
+    pub cash: i32,
+    /// Price paid for each share of the player's own ticker still held.
+    pub shares: Vec<i32>,
+    /// Shares of other players' tickers.
+    pub holdings: Vec<(PlayerId, u32)>,
+    pub index_position: i32,
+    pub debt: i32,
+    pub open_bids: Vec<OpenOrder>,
+    pub open_asks: Vec<OpenOrder>,
+    /// Brackets whose entry has yet to fill, so their exits are not on the book.
+    pub pending_brackets: Vec<PendingBracket>,
+    pub options: Vec<OptionHolding>,
+    /// Unanswered trade offers the player is a party to.
+    pub offers: Vec<TradeOffer>,
+    pub remaining_ms: u64,
+    pub tick_interval_ms: u64,
+}
+
+/// A resting order. `quantity` is what is still unfilled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct OpenOrder {
+    pub price: i32,
+    pub quantity: u32,
+    pub kind: OrderKind,
+    /// The OCO or bracket group the order belongs to, if any.
+    pub group: Option<GroupId>,
+}
+
+/// Exit legs of a bracket, placed once its entry order has fully filled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct PendingBracket {
+    pub group: GroupId,
+    pub take_profit: i32,
+    pub stop_loss: i32,
+    pub quantity: u32,
+}
+
+/// An option the player bought, or wrote when `written`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct OptionHolding {
+    pub contract_id: ContractId,
+    pub spec: OptionSpec,
+    pub written: bool,
+    /// Ticks left until expiry, or `None` for game end.
+    pub ticks_left: Option<u32>,
+    /// Cash locked against a written option until it settles.
+    pub margin: i32,
+}
+
+impl GameState {
+    /// `player_id`'s view of the game, or `None` if they are not playing in it.
+    #[must_use]
+    pub fn player_view(
+        &self,
+        player_id: PlayerId,
+    ) -> Option<PlayerView> {
+        let player = self.players.get(&player_id)?;
+        let orders = |orders: &[Order]| {
+            orders
+                .iter()
+                .map(|o| OpenOrder {
+                    price: o.price,
+                    quantity: o.quantity,
+                    kind: o.kind,
+                    group: o.group,
+                })
+                .collect()
+        };
+
+        let mut holdings: Vec<(PlayerId, u32)> = player.holdings.iter().map(|(&ticker, &qty)| (ticker, qty)).collect();
+        holdings.sort_by_key(|&(ticker, _)| self.seats.iter().position(|&seat| seat == ticker));
+        let mut offers: Vec<TradeOffer> = self
+            .offers
+            .values()
+            .filter(|o| o.seller == player_id || o.buyer == player_id)
+            .cloned()
+            .collect();
+        offers.sort_by_key(|o| o.offer_id.0);
+        let mut pending_brackets: Vec<PendingBracket> = player
+            .groups
+            .iter()
+            .filter_map(|(&group, g)| {
+                g.pending_exits.map(|exits| PendingBracket {
+                    group,
+                    take_profit: exits.take_profit,
+                    stop_loss: exits.stop_loss,
+                    quantity: exits.quantity,
+                })
+            })
+            .collect();
+        pending_brackets.sort_by_key(|b| b.group.0);
+
+        Some(PlayerView {
+            phase: self.phase.clone(),
+            prices: self
+                .seats
+                .iter()
+                .map(|pid| (*pid, self.player_tickers.get(pid).map_or(0, |t| t.current_price)))
+                .collect(),
+            index_price: self.index_price,
+            cash: player.cash,
+            shares: player.shares.clone(),
+            holdings,
+            index_position: player.index_position,
+            debt: player.financing.debt,
+            open_bids: orders(&player.open_bids),
+            open_asks: orders(&player.open_asks),
+            pending_brackets,
+            options: player
+                .options
+                .iter()
+                .map(|o| OptionHolding {
+                    contract_id: o.id,
+                    spec: o.spec,
+                    written: o.written,
+                    ticks_left: o.ticks_left,
+                    margin: o.margin,
+                })
+                .collect(),
+            offers,
+            remaining_ms: self.config.game_duration.saturating_sub(self.elapsed).as_millis() as u64,
+            tick_interval_ms: self.tick_interval.as_millis() as u64,
+        })
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Scales ticker volatility over the course of a game.
+///
+/// Each window applies from its `start` (game time since `Start`) until the
+/// next one begins. Before the first window, or without any, the multiplier
+/// is `1.0`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VolatilitySchedule {
+    pub windows: Vec<VolatilityWindow>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VolatilityWindow {
+    pub start: Duration,
+    pub multiplier: f32,
+}
+
+impl VolatilitySchedule {
+    pub fn multiplier_at(
+        &self,
+        elapsed: Duration,
+    ) -> f32 {
+        self.windows
+            .iter()
+            .filter(|w| w.start <= elapsed)
+            .max_by_key(|w| w.start)
+            .map_or(1.0, |w| w.multiplier)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Regime {
+    #[default]
+    Calm,
+    Turbulent,
+}
+
+/// Two-state Markov chain shared by the whole market, stepped once per tick.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RegimeSwitching {
+    pub calm_multiplier: f32,
+    pub turbulent_multiplier: f32,
+    /// Per-tick probability of leaving the calm regime.
+    pub calm_to_turbulent: f64,
+    /// Per-tick probability of leaving the turbulent regime.
+    pub turbulent_to_calm: f64,
+}
+
+impl Default for RegimeSwitching {
+    fn default() -> Self {
+        Self {
+            calm_multiplier: 1.0,
+            turbulent_multiplier: 2.5,
+            calm_to_turbulent: 0.01,
+            turbulent_to_calm: 0.05,
+        }
+    }
+}
+
+impl RegimeSwitching {
+    pub fn multiplier(
+        &self,
+        regime: Regime,
+    ) -> f32 {
+        match regime {
+            Regime::Calm => self.calm_multiplier,
+            Regime::Turbulent => self.turbulent_multiplier,
+        }
+    }
+
+    /// Draws the regime for the next tick.
+    pub fn step(
+        &self,
+        current: Regime,
+    ) -> Regime {
+        let (leave, other) = match current {
+            Regime::Calm => (self.calm_to_turbulent, Regime::Turbulent),
+            Regime::Turbulent => (self.turbulent_to_calm, Regime::Calm),
+        };
+        if rand::thread_rng().gen_bool(leave.clamp(0.0, 1.0)) {
+            other
+        } else {
+            current
+        }
+    }
+}
@@ -10,6 +10,9 @@ impl Default for PlayerId {
 }
 
 impl PlayerId {
+    /// Reserved ticker id of the composite index in `GameEvent::PriceChanged`.
+    pub const INDEX: PlayerId = PlayerId(uuid::Uuid::nil());
+
     #[must_use]
     pub fn new() -> Self {
         Self(uuid::Uuid::new_v4())
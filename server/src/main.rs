@@ -1,8 +1,11 @@
-use axum::{Router, routing::get};
+use std::sync::Arc;
+
+use axum::Router;
+use axum::routing::{get, post};
 use tower_http::cors::{Any, CorsLayer};
 use tracing::info;
 
-use adapters::{create_app_state, get_queue, handle_connection};
+use adapters::{create_app_state, get_match, get_player_matches, get_queue, get_stats, handle_connection, login, register};
 
 #[tokio::main]
 async fn main() {
@@ -12,15 +15,26 @@ async fn main() {
 
     let cors = CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any);
 
+    let state = create_app_state().await;
     let app = Router::new()
         .route("/ws", get(handle_connection))
         .route("/ping", get(|| async { "pong" }))
         .route("/queue", get(get_queue))
+        .route("/stats", get(get_stats))
+        .route("/matches/{game_id}", get(get_match))
+        .route("/players/{player_id}/matches", get(get_player_matches))
+        .route("/accounts/register", post(register))
+        .route("/accounts/login", post(login))
         .layer(cors)
-        .with_state(create_app_state());
+        .with_state(Arc::clone(&state));
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await.unwrap();
     info!("Server listening on 0.0.0.0:8080");
-    axum::serve(listener, app).await.unwrap();
+    // Fly stops machines with SIGINT; games resume from these snapshots on the next start
+    tokio::select! {
+        result = axum::serve(listener, app) => result.unwrap(),
+        _ = tokio::signal::ctrl_c() => info!("Shutting down"),
+    }
+    state.flush_snapshots().await;
     info!("Server shut down");
 }
@@ -1,6 +1,9 @@
 import { writable, derived, get } from 'svelte/store';
 import { matchmakingStore } from './matchmaking.js';
 
+// Reserved ticker id the server uses for the composite index
+export const INDEX_ID = '00000000-0000-0000-0000-000000000000';
+
 function createGameStore() {
   const { subscribe, set, update } = writable({
     phase: null, // null | countdown | running | ended
@@ -9,9 +12,16 @@ function createGameStore() {
     startingBalance: 0,
     startingPrice: 0,
     gameDuration: 0, // total game duration in seconds
+    tickIntervalMs: 0, // current time between price updates
     gameStartTime: null, // timestamp when game started
     players: {}, // { [playerId]: { priceHistory, currentPrice, purchasePrices, salePrices } }
-    finalBalances: [] // { playerId, balance }
+    displayNames: {}, // { [playerId]: name } for players with an account
+    indexPrice: 0,
+    finalBalances: [], // { playerId, balance }
+    settlements: [], // per-player settlement breakdown from game_ended
+    offers: [], // open trade offers this player is party to
+    tips: [], // insider tips received privately this game
+    revealedTips: [] // every tip handed out, shown once the game ends
   });
 
   let gameStartTime = null;
@@ -28,7 +38,7 @@ function createGameStore() {
       }));
     },
 
-    startGame: (gameId, startingPrice, startingBalance, playerIds, gameDuration) => {
+    startGame: (gameId, startingPrice, startingBalance, playerIds, displayNames, gameDuration, tickIntervalMs) => {
       gameStartTime = Date.now();
       const players = {};
       playerIds.forEach(id => {
@@ -36,7 +46,11 @@ function createGameStore() {
           priceHistory: [{ time: 0, value: startingPrice }],
           currentPrice: startingPrice,
           purchasePrices: [],
-          salePrices: []
+          salePrices: [],
+          financing: 0, // cash from borrowing, repaying, interest and options
+          debt: 0,
+          indexCash: 0, // cash paid for (negative) or received from index trades
+          indexPosition: 0
         };
       });
       set({
@@ -46,13 +60,134 @@ function createGameStore() {
         startingBalance,
         startingPrice,
         gameDuration,
+        tickIntervalMs,
         gameStartTime,
         players,
-        finalBalances: []
+        displayNames: displayNames ?? {},
+        finalBalances: [],
+        settlements: [],
+        offers: [],
+        tips: [],
+        revealedTips: []
+      });
+    },
+
+    // Rebuilds the game from the snapshot sent on reconnecting mid-game
+    resumeGame: (msg, myId) => {
+      const elapsedMs = msg.game_duration_secs * 1000 - msg.remaining_ms;
+      gameStartTime = Date.now() - elapsedMs;
+      const players = {};
+      msg.prices.forEach(([id, price]) => {
+        players[id] = {
+          priceHistory: [{ time: elapsedMs / 1000, value: price }],
+          currentPrice: price,
+          purchasePrices: [],
+          salePrices: [],
+          financing: 0,
+          debt: 0,
+          indexCash: 0,
+          indexPosition: 0
+        };
+      });
+      if (players[myId]) {
+        // Everything but the shares still held is folded into financing so the balance matches our cash
+        const costBasis = msg.shares.reduce((a, b) => a + b, 0);
+        players[myId] = {
+          ...players[myId],
+          purchasePrices: msg.shares,
+          financing: msg.cash + costBasis - msg.starting_balance,
+          debt: msg.debt,
+          indexPosition: msg.index_position
+        };
+      }
+      const phases = { Running: 'running', Ended: 'ended' };
+      set({
+        phase: phases[msg.phase] ?? 'countdown',
+        gameId: msg.game_id,
+        countdown: 0,
+        startingBalance: msg.starting_balance,
+        startingPrice: msg.prices[0]?.[1] ?? 0,
+        gameDuration: msg.game_duration_secs,
+        tickIntervalMs: msg.tick_interval_ms,
+        gameStartTime,
+        players,
+        displayNames: msg.display_names ?? {},
+        indexPrice: msg.index_price,
+        finalBalances: [],
+        settlements: [],
+        offers: msg.offers,
+        tips: [],
+        revealedTips: []
+      });
+    },
+
+    updateFinancing: (playerId, cashDelta, debt) => {
+      update(s => {
+        const player = s.players[playerId];
+        if (!player) return s;
+        return {
+          ...s,
+          players: {
+            ...s.players,
+            [playerId]: { ...player, financing: player.financing + cashDelta, debt }
+          }
+        };
+      });
+    },
+
+    // Market maker quote on a player's ticker; a null side is pulled
+    setQuote: (playerId, quote, inventory) => {
+      update(s => {
+        const player = s.players[playerId];
+        if (!player) return s;
+        return {
+          ...s,
+          players: {
+            ...s.players,
+            [playerId]: { ...player, quote, makerInventory: inventory }
+          }
+        };
       });
     },
 
+    adjustCash: (playerId, cashDelta) => {
+      update(s => {
+        const player = s.players[playerId];
+        if (!player) return s;
+        return {
+          ...s,
+          players: {
+            ...s.players,
+            [playerId]: { ...player, financing: player.financing + cashDelta }
+          }
+        };
+      });
+    },
+
+    tradeIndex: (playerId, side, quantity, price, position) => {
+      update(s => {
+        const player = s.players[playerId];
+        if (!player) return s;
+        const cash = (side === 'bid' ? -1 : 1) * price * quantity;
+        return {
+          ...s,
+          players: {
+            ...s.players,
+            [playerId]: { ...player, indexCash: player.indexCash + cash, indexPosition: position }
+          }
+        };
+      });
+    },
+
+    setTickInterval: (tickIntervalMs) => {
+      update(s => ({ ...s, tickIntervalMs }));
+    },
+
     updatePrice: (playerId, price) => {
+      if (playerId === INDEX_ID) {
+        update(s => ({ ...s, indexPrice: price }));
+        return;
+      }
       update(s => {
         const elapsed = gameStartTime ? (Date.now() - gameStartTime) / 1000 : 0;
         const player = s.players[playerId];
@@ -71,7 +206,7 @@ function createGameStore() {
       });
     },
 
-    fillBid: (playerId, fillPrice) => {
+    fillBid: (playerId, fillPrice, quantity = 1) => {
       update(s => {
         const player = s.players[playerId];
         if (!player) return s;
@@ -81,14 +216,14 @@ function createGameStore() {
             ...s.players,
             [playerId]: {
               ...player,
-              purchasePrices: [...player.purchasePrices, fillPrice]
+              purchasePrices: [...player.purchasePrices, ...Array(quantity).fill(fillPrice)]
             }
           }
         };
       });
     },
 
-    fillAsk: (playerId, fillPrice) => {
+    fillAsk: (playerId, fillPrice, quantity = 1) => {
       update(s => {
         const player = s.players[playerId];
         if (!player) return s;
@@ -98,18 +233,48 @@ function createGameStore() {
             ...s.players,
             [playerId]: {
               ...player,
-              salePrices: [...player.salePrices, fillPrice]
+              salePrices: [...player.salePrices, ...Array(quantity).fill(fillPrice)]
             }
           }
         };
       });
     },
 
-    endGame: (finalBalances) => {
+    addTip: (tip) => {
+      update(s => ({ ...s, tips: [...s.tips, tip] }));
+    },
+
+    revealTips: (tips) => {
+      update(s => ({ ...s, revealedTips: tips }));
+    },
+
+    addOffer: (offer) => {
+      update(s => ({ ...s, offers: [...s.offers, offer] }));
+    },
+
+    // Drops the offer; an accepted one moves the shares' price between the parties
+    resolveOffer: (offerId, status) => {
+      update(s => {
+        const offer = s.offers.find(o => o.offer_id === offerId);
+        if (!offer) return s;
+        const offers = s.offers.filter(o => o.offer_id !== offerId);
+        if (status !== 'accepted') return { ...s, offers };
+        const cost = offer.price * offer.quantity;
+        const players = { ...s.players };
+        for (const [playerId, delta] of [[offer.seller, cost], [offer.buyer, -cost]]) {
+          const player = players[playerId];
+          if (player) players[playerId] = { ...player, financing: player.financing + delta };
+        }
+        return { ...s, offers, players };
+      });
+    },
+
+    endGame: (finalBalances, settlements = []) => {
       update(s => ({
         ...s,
         phase: 'ended',
-        finalBalances: finalBalances.map(([playerId, balance]) => ({ playerId, balance }))
+        finalBalances: finalBalances.map(([playerId, balance]) => ({ playerId, balance })),
+        settlements
       }));
     },
 
@@ -122,9 +287,15 @@ function createGameStore() {
         startingBalance: 0,
         startingPrice: 0,
         gameDuration: 0,
+        tickIntervalMs: 0,
         gameStartTime: null,
         players: {},
-        finalBalances: []
+        displayNames: {},
+        finalBalances: [],
+        settlements: [],
+        offers: [],
+        tips: [],
+        revealedTips: []
       });
     },
 
@@ -139,11 +310,11 @@ export function computePlayerStats(player, startingBalance) {
   const shares = player.purchasePrices.length - player.salePrices.length;
   const totalPurchased = player.purchasePrices.reduce((a, b) => a + b, 0);
   const totalSold = player.salePrices.reduce((a, b) => a + b, 0);
-  const balance = startingBalance - totalPurchased + totalSold;
+  const balance = startingBalance - totalPurchased + totalSold + (player.financing ?? 0) + (player.indexCash ?? 0);
   const costBasis = player.purchasePrices.length > 0
     ? Math.round(totalPurchased / player.purchasePrices.length)
     : null;
-  return { balance, shares, costBasis };
+  return { balance, shares, costBasis, debt: player.debt ?? 0, indexPosition: player.indexPosition ?? 0 };
 }
 
 // Derived store for current player's P/L calculation
@@ -153,6 +324,7 @@ export const profitLoss = derived(gameStore, $game => {
   const player = $game.players[myId];
   if (!player) return 0;
   const stats = computePlayerStats(player, $game.startingBalance);
-  const portfolioValue = stats.balance + (stats.shares * player.currentPrice);
+  const portfolioValue = stats.balance + (stats.shares * player.currentPrice)
+    + (stats.indexPosition * $game.indexPrice) - stats.debt;
   return portfolioValue - $game.startingBalance;
 });
@@ -42,6 +42,10 @@ function createMatchmakingStore() {
       });
     },
 
+    setPlayerId: (playerId) => {
+      update(s => ({ ...s, playerId }));
+    },
+
     setAlreadyQueued: () => {
       update(s => ({ ...s, error: 'Already in queue' }));
     },
@@ -6,7 +6,16 @@ let reconnectTimeout = null;
 const RECONNECT_DELAY = 2000;
 
 function getWsUrl() {
-  return import.meta.env.VITE_WS_URL || 'ws://localhost:8080/ws';
+  const url = import.meta.env.VITE_WS_URL || 'ws://localhost:8080/ws';
+  // Signed-in players connect as their account; everyone else as a guest.
+  // A resume token from an earlier connection carries on as that player.
+  const params = new URLSearchParams();
+  const token = localStorage.getItem('sessionToken');
+  const resume = sessionStorage.getItem('resumeToken');
+  if (token) params.set('token', token);
+  if (resume) params.set('resume', resume);
+  const query = params.toString();
+  return query ? `${url}?${query}` : url;
 }
 
 export function connect() {
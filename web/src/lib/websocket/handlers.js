@@ -1,5 +1,6 @@
 import { matchmakingStore } from '../stores/matchmaking.js';
 import { gameStore } from '../stores/game.js';
+import { send } from './client.js';
 
 // Parse and handle incoming server messages
 export function handleMessage(data) {
@@ -13,6 +14,7 @@ export function handleMessage(data) {
 
   // Check for game notifications (tagged with "type")
   if (msg.type) {
+    checkSequence(msg);
     handleGameNotification(msg);
     return;
   }
@@ -21,6 +23,32 @@ export function handleMessage(data) {
   handleMatchmakingMessage(msg);
 }
 
+// Last sequence number seen per game; each game numbers our stream from 1
+const lastSeq = new Map();
+
+// Games we have asked for a snapshot of and not yet received one
+const resyncing = new Set();
+
+// Id the server bound this connection to
+let connectedAs = null;
+
+function checkSequence(msg) {
+  if (msg.seq === undefined) return;
+  // A resume snapshot replaces whatever was missed
+  if (msg.type === 'game_resumed') {
+    lastSeq.set(msg.game_id, msg.seq);
+    resyncing.delete(msg.game_id);
+    return;
+  }
+  const expected = (lastSeq.get(msg.game_id) ?? 0) + 1;
+  if (msg.seq !== expected && !resyncing.has(msg.game_id)) {
+    console.warn(`Missed notifications for game ${msg.game_id}: expected #${expected}, got #${msg.seq}; resyncing`);
+    resyncing.add(msg.game_id);
+    send({ type: 'resync', game_id: msg.game_id });
+  }
+  lastSeq.set(msg.game_id, msg.seq);
+}
+
 function handleMatchmakingMessage(msg) {
   if (msg.Enqueued !== undefined) {
     matchmakingStore.setEnqueued(msg.Enqueued);
@@ -37,6 +65,21 @@ function handleMatchmakingMessage(msg) {
 
 function handleGameNotification(msg) {
   switch (msg.type) {
+    case 'connected':
+      connectedAs = msg.player_id;
+      sessionStorage.setItem('resumeToken', msg.resume_token);
+      break;
+
+    // Resume tokens are short-lived, so the server keeps replacing ours
+    case 'resume_token':
+      sessionStorage.setItem('resumeToken', msg.resume_token);
+      break;
+
+    case 'game_resumed':
+      matchmakingStore.setPlayerId(connectedAs);
+      gameStore.resumeGame(msg, connectedAs);
+      break;
+
     case 'countdown':
       gameStore.setCountdown(msg.game_id, msg.remaining);
       break;
@@ -47,24 +90,72 @@ function handleGameNotification(msg) {
         msg.starting_price,
         msg.starting_balance,
         msg.players,
-        msg.game_duration_secs
+        msg.display_names,
+        msg.game_duration_secs,
+        msg.tick_interval_ms
       );
       break;
 
+    case 'tick_interval_changed':
+      gameStore.setTickInterval(msg.interval_ms);
+      break;
+
+    case 'auction_cleared':
     case 'price_changed':
       gameStore.updatePrice(msg.player_id, msg.price);
       break;
 
+    case 'quote_updated':
+      gameStore.setQuote(msg.player_id, msg.quote, msg.inventory);
+      break;
+
     case 'bid_filled':
-      gameStore.fillBid(msg.player_id, msg.bid_value);
+      gameStore.fillBid(msg.player_id, msg.bid_value, msg.quantity);
       break;
 
     case 'ask_filled':
-      gameStore.fillAsk(msg.player_id, msg.ask_value);
+      gameStore.fillAsk(msg.player_id, msg.ask_value, msg.quantity);
+      break;
+
+    case 'index_traded':
+      gameStore.tradeIndex(msg.player_id, msg.side, msg.quantity, msg.price, msg.position);
+      break;
+
+    case 'option_traded':
+      gameStore.adjustCash(msg.player_id, msg.written ? msg.premium : -msg.premium);
+      break;
+
+    case 'option_settled':
+      gameStore.adjustCash(msg.player_id, msg.cash_delta);
+      break;
+
+    case 'insider_tip':
+      gameStore.addTip({ ticker: msg.ticker, direction: msg.direction, inTicks: msg.in_ticks });
+      break;
+
+    case 'insider_tips_revealed':
+      gameStore.revealTips(msg.tips);
+      break;
+
+    case 'offer_proposed':
+      gameStore.addOffer(msg.offer);
+      break;
+
+    case 'offer_resolved':
+      gameStore.resolveOffer(msg.offer_id, msg.status);
+      break;
+
+    case 'financing_updated':
+      gameStore.updateFinancing(msg.player_id, msg.cash_delta, msg.debt);
       break;
 
     case 'game_ended':
-      gameStore.endGame(msg.final_balances);
+      gameStore.endGame(msg.final_balances, msg.settlements);
+      break;
+
+    case 'game_aborted':
+      console.error(`Game ${msg.game_id} was stopped by a server error`);
+      gameStore.reset();
       break;
   }
 }